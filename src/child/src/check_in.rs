@@ -0,0 +1,212 @@
+use candid::Principal;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// A check-in code contains the event, the attendee, the time it expires and a HMAC-SHA256 signature over them
+// formatted as `<event>:<attendee>:<expires_at>:<hex signature>` so it can be shown as a QR code
+pub struct CheckInCode {
+    pub event_identifier: Principal,
+    pub attendee: Principal,
+    pub expires_at: u64,
+}
+
+// The reasons a check-in code is rejected
+#[derive(Debug, PartialEq)]
+pub enum CheckInCodeError {
+    // The code is malformed or the signature doesnt match
+    Invalid,
+    // The code is signed for another event
+    WrongEvent,
+    Expired,
+}
+
+impl CheckInCode {
+    pub fn sign(&self, secret: &[u8]) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.event_identifier,
+            self.attendee,
+            self.expires_at,
+            hex::encode(self.mac(secret).finalize().into_bytes())
+        )
+    }
+
+    // Returns the code when the format and the signature are valid and the code is for the event and not expired
+    pub fn verify(
+        code: &str,
+        event_identifier: &Principal,
+        secret: &[u8],
+        now: u64,
+    ) -> Result<Self, CheckInCodeError> {
+        let check_in_code = Self::parse(code, secret).ok_or(CheckInCodeError::Invalid)?;
+
+        if &check_in_code.event_identifier != event_identifier {
+            return Err(CheckInCodeError::WrongEvent);
+        }
+
+        if check_in_code.expires_at < now {
+            return Err(CheckInCodeError::Expired);
+        }
+
+        Ok(check_in_code)
+    }
+
+    // Returns the code when the format and the signature are valid
+    fn parse(code: &str, secret: &[u8]) -> Option<Self> {
+        if secret.is_empty() {
+            return None;
+        }
+
+        let mut parts = code.trim().split(':');
+        let event_identifier = Principal::from_text(parts.next()?).ok()?;
+        let attendee = Principal::from_text(parts.next()?).ok()?;
+        let expires_at = parts.next()?.parse::<u64>().ok()?;
+        let signature = hex::decode(parts.next()?).ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let check_in_code = Self {
+            event_identifier,
+            attendee,
+            expires_at,
+        };
+        // The signature is compared in constant time
+        match check_in_code.mac(secret).verify_slice(&signature) {
+            Ok(_) => Some(check_in_code),
+            Err(_) => None,
+        }
+    }
+
+    fn mac(&self, secret: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+        mac.update(&self.message());
+        mac
+    }
+
+    // The principals are length prefixed so different principals can't result in the same message, the expiry has a fixed length
+    fn message(&self) -> Vec<u8> {
+        let mut message = vec![];
+        for principal in [&self.event_identifier, &self.attendee] {
+            message.push(principal.as_slice().len() as u8);
+            message.extend_from_slice(principal.as_slice());
+        }
+        message.extend_from_slice(&self.expires_at.to_be_bytes());
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SECRET: &[u8] = b"secret";
+    static EXPIRES_AT: u64 = 1_000;
+
+    fn principal(byte: u8) -> Principal {
+        Principal::from_slice(&[byte; 29])
+    }
+
+    fn check_in_code() -> CheckInCode {
+        CheckInCode {
+            event_identifier: principal(1),
+            attendee: principal(2),
+            expires_at: EXPIRES_AT,
+        }
+    }
+
+    fn verify(code: &str) -> Result<CheckInCode, CheckInCodeError> {
+        CheckInCode::verify(code, &principal(1), SECRET, EXPIRES_AT - 1)
+    }
+
+    #[test]
+    fn signed_code_is_verified() {
+        let verified = verify(&check_in_code().sign(SECRET)).unwrap();
+
+        assert_eq!(verified.event_identifier, principal(1));
+        assert_eq!(verified.attendee, principal(2));
+        assert_eq!(verified.expires_at, EXPIRES_AT);
+    }
+
+    #[test]
+    fn tampered_code_is_rejected() {
+        let code = check_in_code().sign(SECRET);
+        let signature = code.rsplit(':').next().unwrap();
+
+        // Another attendee or a later expiry with the signature of the original code
+        let other_attendee = format!(
+            "{}:{}:{}:{}",
+            principal(1),
+            principal(3),
+            EXPIRES_AT,
+            signature
+        );
+        let later_expiry = format!(
+            "{}:{}:{}:{}",
+            principal(1),
+            principal(2),
+            EXPIRES_AT * 2,
+            signature
+        );
+
+        assert_eq!(
+            verify(&other_attendee).err(),
+            Some(CheckInCodeError::Invalid)
+        );
+        assert_eq!(verify(&later_expiry).err(), Some(CheckInCodeError::Invalid));
+        assert_eq!(
+            verify(&check_in_code().sign(b"another secret")).err(),
+            Some(CheckInCodeError::Invalid)
+        );
+        assert_eq!(
+            verify(&format!("{}:extra", code)).err(),
+            Some(CheckInCodeError::Invalid)
+        );
+        assert_eq!(verify("").err(), Some(CheckInCodeError::Invalid));
+    }
+
+    #[test]
+    fn code_for_another_event_is_rejected() {
+        let code = CheckInCode {
+            event_identifier: principal(4),
+            ..check_in_code()
+        }
+        .sign(SECRET);
+
+        assert_eq!(verify(&code).err(), Some(CheckInCodeError::WrongEvent));
+    }
+
+    #[test]
+    fn expired_code_is_rejected() {
+        let code = check_in_code().sign(SECRET);
+
+        assert!(CheckInCode::verify(&code, &principal(1), SECRET, EXPIRES_AT).is_ok());
+        assert_eq!(
+            CheckInCode::verify(&code, &principal(1), SECRET, EXPIRES_AT + 1).err(),
+            Some(CheckInCodeError::Expired)
+        );
+    }
+
+    #[test]
+    fn code_is_rejected_without_a_secret() {
+        assert_eq!(
+            verify(&check_in_code().sign(b"")).err(),
+            Some(CheckInCodeError::Invalid)
+        );
+    }
+
+    // The child canisters share the secret of the parent canister and a migrated event keeps its identifier,
+    // so a code issued by the source canister is verified by the target canister
+    #[test]
+    fn code_is_verified_after_the_event_is_migrated() {
+        let parent_secret = b"parent secret".to_vec();
+        let source_secret = parent_secret.clone();
+        let target_secret = parent_secret;
+
+        let code = check_in_code().sign(&source_secret);
+
+        assert!(CheckInCode::verify(&code, &principal(1), &target_secret, EXPIRES_AT - 1).is_ok());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::methods::auth;
+use crate::methods::is_parent;
 use candid::Principal;
 
 use ic_cdk::{
@@ -21,29 +21,19 @@ use ic_scalable_canister::{
     store::Data,
 };
 
-use crate::{
-    store::{ENTRIES, STABLE_DATA},
-    IDENTIFIER_KIND,
-};
+use crate::store::{Store, ENTRIES, STABLE_DATA};
 
 // This call get triggered when a new canister is spun up
-// the data is passed along to the new canister as a byte array
-#[update(guard = "auth")]
-fn add_entry_by_parent(entry: Vec<u8>) -> Result<(), ApiError> {
-    STABLE_DATA.with(|v| {
-        ENTRIES.with(|entries| {
-            Data::add_entry_by_parent(
-                v,
-                entries,
-                caller(),
-                entry,
-                Some(IDENTIFIER_KIND.to_string()),
-            )
-        })
-    })
+// the data is passed along to the new canister as a byte array, with the sibling canister that forwarded it
+// the identifier of the stored entry is returned
+#[update(guard = "is_parent")]
+fn add_entry_by_parent(
+    entry: Vec<u8>,
+    forwarded_by: Option<Principal>,
+) -> Result<Principal, ApiError> {
+    Store::add_entry_by_parent(entry, forwarded_by)
 }
 
-// Method to accept cycles when send to this canister
 #[update]
 fn accept_cycles() -> u64 {
     ic_methods::accept_cycles()
@@ -68,11 +58,12 @@ pub fn __export_did_tmp_() -> String {
     use ic_canister_backup::models::*;
     use ic_cdk::api::management_canister::http_request::HttpResponse;
     use ic_scalable_canister::ic_scalable_misc::enums::api_error_type::ApiError;
-    use ic_scalable_canister::ic_scalable_misc::enums::filter_type::FilterType;
     use ic_scalable_canister::ic_scalable_misc::enums::privacy_type::Privacy;
     use ic_scalable_canister::ic_scalable_misc::models::http_models::HttpRequest;
-    use ic_scalable_canister::ic_scalable_misc::models::paged_response_models::PagedResponse;
+    use shared::chunk_models::*;
+    use shared::event_facets::*;
     use shared::event_models::*;
+    use shared::icrc_models::*;
     export_service!();
     __export_service()
 }
@@ -0,0 +1,450 @@
+use candid::{
+    utils::{ArgumentDecoder, ArgumentEncoder},
+    Nat, Principal,
+};
+
+use shared::event_models::TicketPaymentMethod;
+use shared::icrc_models::{
+    event_subaccount, ticket_payment_subaccount, Account, TransferArg, TransferError,
+    TransferFromArgs, TransferFromError,
+};
+
+// Inter-canister calls to an ICRC-1 / ICRC-2 ledger canister, the errors are returned as readable strings
+// the ticket payment transfers are sent with a created_at_time and memo, so a resubmitted transfer is deduplicated by the ledger
+pub struct Ledger {
+    // The ledger canister
+    pub ledger: Principal,
+    // The canister that owns the event and payment subaccounts (this canister)
+    pub canister: Principal,
+}
+
+impl Ledger {
+    pub fn new(ledger: Principal, canister: Principal) -> Self {
+        Self { ledger, canister }
+    }
+
+    // Collect a ticket payment on the subaccount of the event, returns the block index of the transfer
+    pub async fn collect(
+        &self,
+        event_identifier: &Principal,
+        payer: Principal,
+        amount: Nat,
+        payment_method: &TicketPaymentMethod,
+        created_at_time: u64,
+        memo: Vec<u8>,
+    ) -> Result<Nat, String> {
+        let event_account = Account {
+            owner: self.canister,
+            subaccount: Some(event_subaccount(event_identifier)),
+        };
+
+        match payment_method {
+            // The buyer approved this canister to transfer the price from their account
+            TicketPaymentMethod::Icrc2Approval { from_subaccount } => {
+                self.transfer_from(TransferFromArgs {
+                    spender_subaccount: None,
+                    from: Account {
+                        owner: payer,
+                        subaccount: from_subaccount.clone(),
+                    },
+                    to: event_account,
+                    amount,
+                    fee: None,
+                    memo: Some(memo),
+                    created_at_time: Some(created_at_time),
+                })
+                .await
+            }
+            // The buyer transferred the price plus the fee to their payment subaccount, which is moved to the event subaccount
+            TicketPaymentMethod::Icrc1Transfer => {
+                self.transfer(TransferArg {
+                    from_subaccount: Some(ticket_payment_subaccount(event_identifier, &payer)),
+                    to: event_account,
+                    fee: None,
+                    created_at_time: Some(created_at_time),
+                    memo: Some(memo),
+                    amount,
+                })
+                .await
+            }
+        }
+    }
+
+    // Return what is left on the payment subaccount of the buyer after an ICRC-1 payment is collected, minus the ledger fee
+    // returns the block index of the transfer, or `None` when nothing is left to return
+    pub async fn return_overpayment(
+        &self,
+        event_identifier: &Principal,
+        payer: Principal,
+        to: Account,
+        created_at_time: u64,
+        memo: Vec<u8>,
+    ) -> Result<Option<Nat>, String> {
+        let payment_subaccount = ticket_payment_subaccount(event_identifier, &payer);
+        let fee = self.fee().await?;
+        let balance = self
+            .balance_of(Account {
+                owner: self.canister,
+                subaccount: Some(payment_subaccount.clone()),
+            })
+            .await?;
+
+        if balance <= fee {
+            return Ok(None);
+        }
+
+        self.transfer(TransferArg {
+            from_subaccount: Some(payment_subaccount),
+            to,
+            fee: Some(fee.clone()),
+            created_at_time: Some(created_at_time),
+            memo: Some(memo),
+            amount: balance - fee,
+        })
+        .await
+        .map(Some)
+    }
+
+    // Refund a ticket payment minus the ledger fee from the subaccount of the event, returns the block index of the transfer
+    pub async fn refund(
+        &self,
+        event_identifier: &Principal,
+        to: Account,
+        amount: Nat,
+        created_at_time: u64,
+        memo: Vec<u8>,
+    ) -> Result<Nat, String> {
+        let fee = self.fee().await?;
+        if amount <= fee {
+            return Err("The payment is lower than the ledger fee".to_string());
+        }
+
+        self.transfer(TransferArg {
+            from_subaccount: Some(event_subaccount(event_identifier)),
+            to,
+            fee: Some(fee.clone()),
+            created_at_time: Some(created_at_time),
+            memo: Some(memo),
+            amount: amount - fee,
+        })
+        .await
+    }
+
+    pub async fn fee(&self) -> Result<Nat, String> {
+        let result: Result<(Nat,), _> = self.call("icrc1_fee", ()).await;
+        result.map(|(fee,)| fee)
+    }
+
+    pub async fn balance_of(&self, account: Account) -> Result<Nat, String> {
+        let result: Result<(Nat,), _> = self.call("icrc1_balance_of", (account,)).await;
+        result.map(|(balance,)| balance)
+    }
+
+    // Returns the block index of the transfer, a duplicate returns the block index of the original transfer
+    pub async fn transfer(&self, arg: TransferArg) -> Result<Nat, String> {
+        let result: Result<(Result<Nat, TransferError>,), _> =
+            self.call("icrc1_transfer", (arg,)).await;
+
+        match result {
+            Ok((Ok(block_index),)) => Ok(block_index),
+            Ok((Err(TransferError::Duplicate { duplicate_of }),)) => Ok(duplicate_of),
+            Ok((Err(err),)) => Err(format!("{:?}", err)),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Returns the block index of the transfer, a duplicate returns the block index of the original transfer
+    pub async fn transfer_from(&self, args: TransferFromArgs) -> Result<Nat, String> {
+        let result: Result<(Result<Nat, TransferFromError>,), _> =
+            self.call("icrc2_transfer_from", (args,)).await;
+
+        match result {
+            Ok((Ok(block_index),)) => Ok(block_index),
+            Ok((Err(TransferFromError::Duplicate { duplicate_of }),)) => Ok(duplicate_of),
+            Ok((Err(err),)) => Err(format!("{:?}", err)),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[cfg(not(test))]
+    async fn call<T: ArgumentEncoder, R: for<'a> ArgumentDecoder<'a>>(
+        &self,
+        method: &str,
+        args: T,
+    ) -> Result<R, String> {
+        ic_cdk::api::call::call(self.ledger, method, args)
+            .await
+            .map_err(|err| err.1)
+    }
+
+    // The ledger calls are answered by the mock ledger in the tests
+    #[cfg(test)]
+    async fn call<T: ArgumentEncoder, R: for<'a> ArgumentDecoder<'a>>(
+        &self,
+        method: &str,
+        args: T,
+    ) -> Result<R, String> {
+        tests::call_mock_ledger(self.ledger, self.canister, method, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        future::Future,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    use candid::{decode_args, decode_one, encode_args, encode_one};
+    use mock_ledger::MockLedger;
+    use shared::icrc_models::ApproveArgs;
+
+    use super::*;
+
+    static FEE: u64 = 10;
+    // The principal of the mock ledger, a call to another ledger fails
+    static MOCK_LEDGER_BYTE: u8 = 9;
+
+    thread_local! {
+        static MOCK_LEDGER: RefCell<MockLedger> = RefCell::new(MockLedger::new(Nat::from(FEE)));
+    }
+
+    // Answer a ledger call with the mock ledger, the arguments and reply are candid encoded like an inter-canister call
+    pub fn call_mock_ledger<T: ArgumentEncoder, R: for<'a> ArgumentDecoder<'a>>(
+        ledger_canister: Principal,
+        caller: Principal,
+        method: &str,
+        args: T,
+    ) -> Result<R, String> {
+        if ledger_canister != principal(MOCK_LEDGER_BYTE) {
+            return Err(format!("unknown ledger canister {}", ledger_canister));
+        }
+
+        let bytes = encode_args(args).map_err(|err| err.to_string())?;
+        let reply = MOCK_LEDGER
+            .with(|ledger| {
+                let mut ledger = ledger.borrow_mut();
+                match method {
+                    "icrc1_fee" => encode_one(ledger.fee.clone()),
+                    "icrc1_balance_of" => {
+                        encode_one(ledger.balance_of(&decode_one::<Account>(&bytes)?))
+                    }
+                    "icrc1_transfer" => {
+                        encode_one(ledger.transfer(caller, decode_one::<TransferArg>(&bytes)?))
+                    }
+                    "icrc2_transfer_from" => encode_one(
+                        ledger.transfer_from(caller, decode_one::<TransferFromArgs>(&bytes)?),
+                    ),
+                    _ => panic!("unexpected ledger method {}", method),
+                }
+            })
+            .map_err(|err| err.to_string())?;
+
+        decode_args(&reply).map_err(|err| err.to_string())
+    }
+
+    // The mock ledger answers right away, so the futures are ready on the first poll
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn noop_raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                noop_raw_waker()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+
+        let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+        let mut future = Box::pin(future);
+        match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the ledger call didnt complete"),
+        }
+    }
+
+    fn principal(byte: u8) -> Principal {
+        Principal::from_slice(&[byte; 29])
+    }
+
+    fn account(owner: Principal, subaccount: Option<Vec<u8>>) -> Account {
+        Account { owner, subaccount }
+    }
+
+    fn mint(account: Account, amount: u64) {
+        MOCK_LEDGER.with(|ledger| ledger.borrow_mut().mint(account, Nat::from(amount)));
+    }
+
+    fn balance(account: Account) -> Nat {
+        MOCK_LEDGER.with(|ledger| ledger.borrow().balance_of(&account))
+    }
+
+    // (ledger, buyer, child canister, event)
+    fn setup() -> (Ledger, Principal, Principal, Principal) {
+        let (buyer, child, event) = (principal(1), principal(2), principal(3));
+        (
+            Ledger::new(principal(MOCK_LEDGER_BYTE), child),
+            buyer,
+            child,
+            event,
+        )
+    }
+
+    #[test]
+    fn collects_icrc2_payment_on_the_event_subaccount() {
+        let (ledger, buyer, child, event) = setup();
+        mint(account(buyer, None), 1_000);
+        MOCK_LEDGER
+            .with(|mock| {
+                mock.borrow_mut().approve(
+                    buyer,
+                    ApproveArgs {
+                        from_subaccount: None,
+                        spender: account(child, None),
+                        amount: Nat::from(510_u64),
+                        expected_allowance: None,
+                        expires_at: None,
+                        fee: None,
+                        memo: None,
+                        created_at_time: None,
+                    },
+                )
+            })
+            .unwrap();
+
+        let payment_method = TicketPaymentMethod::Icrc2Approval {
+            from_subaccount: None,
+        };
+        assert!(block_on(ledger.collect(
+            &event,
+            buyer,
+            Nat::from(500_u64),
+            &payment_method,
+            1,
+            vec![1]
+        ))
+        .is_ok());
+
+        assert_eq!(
+            balance(account(child, Some(event_subaccount(&event)))),
+            Nat::from(500_u64)
+        );
+        // The approval fee and the transfer fee
+        assert_eq!(balance(account(buyer, None)), Nat::from(480_u64));
+    }
+
+    #[test]
+    fn resubmitted_payment_returns_the_original_block() {
+        let (ledger, buyer, child, event) = setup();
+        mint(
+            account(child, Some(ticket_payment_subaccount(&event, &buyer))),
+            510,
+        );
+
+        let collect = || {
+            block_on(ledger.collect(
+                &event,
+                buyer,
+                Nat::from(500_u64),
+                &TicketPaymentMethod::Icrc1Transfer,
+                1,
+                vec![1],
+            ))
+        };
+        let block_index = collect().unwrap();
+
+        assert_eq!(collect(), Ok(block_index));
+        assert_eq!(
+            balance(account(child, Some(event_subaccount(&event)))),
+            Nat::from(500_u64)
+        );
+    }
+
+    #[test]
+    fn icrc1_payment_needs_the_price_and_fee() {
+        let (ledger, buyer, child, event) = setup();
+        mint(
+            account(child, Some(ticket_payment_subaccount(&event, &buyer))),
+            505,
+        );
+
+        let result = block_on(ledger.collect(
+            &event,
+            buyer,
+            Nat::from(500_u64),
+            &TicketPaymentMethod::Icrc1Transfer,
+            1,
+            vec![1],
+        ));
+
+        assert!(result.unwrap_err().contains("InsufficientFunds"));
+        assert_eq!(
+            balance(account(child, Some(event_subaccount(&event)))),
+            Nat::from(0_u64)
+        );
+    }
+
+    #[test]
+    fn returns_the_icrc1_overpayment() {
+        let (ledger, buyer, child, event) = setup();
+        let payment_account = account(child, Some(ticket_payment_subaccount(&event, &buyer)));
+        mint(payment_account.clone(), 1_000);
+
+        assert!(block_on(ledger.collect(
+            &event,
+            buyer,
+            Nat::from(500_u64),
+            &TicketPaymentMethod::Icrc1Transfer,
+            1,
+            vec![1]
+        ))
+        .is_ok());
+        assert_eq!(balance(payment_account.clone()), Nat::from(490_u64));
+
+        let returned =
+            block_on(ledger.return_overpayment(&event, buyer, account(buyer, None), 2, vec![1]));
+        assert!(matches!(returned, Ok(Some(_))));
+        assert_eq!(balance(payment_account), Nat::from(0_u64));
+        assert_eq!(balance(account(buyer, None)), Nat::from(480_u64));
+
+        // Nothing is left to return
+        let returned =
+            block_on(ledger.return_overpayment(&event, buyer, account(buyer, None), 3, vec![1]));
+        assert_eq!(returned, Ok(None));
+    }
+
+    #[test]
+    fn resubmitted_refund_is_refunded_once() {
+        let (ledger, buyer, child, event) = setup();
+        let event_account = account(child, Some(event_subaccount(&event)));
+        mint(event_account.clone(), 1_000);
+
+        let refund = |created_at_time: u64| {
+            block_on(ledger.refund(
+                &event,
+                account(buyer, None),
+                Nat::from(500_u64),
+                created_at_time,
+                vec![1],
+            ))
+        };
+        let block_index = refund(1).unwrap();
+
+        // An interrupted refund is resubmitted with the same created_at_time
+        assert_eq!(refund(1), Ok(block_index));
+        assert_eq!(balance(account(buyer, None)), Nat::from(490_u64));
+        assert_eq!(balance(event_account), Nat::from(500_u64));
+    }
+
+    #[test]
+    fn refund_needs_more_than_the_fee() {
+        let (ledger, buyer, child, event) = setup();
+        mint(account(child, Some(event_subaccount(&event))), 1_000);
+
+        let result =
+            block_on(ledger.refund(&event, account(buyer, None), Nat::from(FEE), 1, vec![1]));
+
+        assert!(result.is_err());
+        assert_eq!(balance(account(buyer, None)), Nat::from(0_u64));
+    }
+}
@@ -1,7 +1,9 @@
-pub static IDENTIFIER_KIND: &str = "evt";
+pub use shared::IDENTIFIER_KIND;
 
 pub mod backup;
+mod check_in;
 pub mod default;
+mod ledger;
 pub mod methods;
 mod stable_backup;
 pub mod store;
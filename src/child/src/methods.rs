@@ -1,14 +1,22 @@
 use candid::Principal;
 use ic_cdk::{caller, query, update};
-use ic_scalable_canister::ic_scalable_misc::{
-    enums::{api_error_type::ApiError, filter_type::FilterType, privacy_type::Privacy},
-    models::paged_response_models::PagedResponse,
+use ic_scalable_canister::ic_scalable_misc::enums::{
+    api_error_type::ApiError, privacy_type::Privacy,
 };
 
 use crate::store::STABLE_DATA;
 
 use super::store::Store;
-use shared::event_models::{EventFilter, EventResponse, EventSort, PostEvent, UpdateEvent};
+use shared::chunk_models::{ChunkSnapshot, ChunkedData, ChunkedDataRequest};
+use shared::event_facets::EventFacetRequest;
+use shared::event_models::{
+    AddEventResponse, CheckIn, CheckInStats, EditEventError, Event, EventFilterExpression,
+    EventHostAbility, EventHostRole, EventPatch, EventProfileKind, EventResponse, EventSort,
+    EventsCount, MetadataField, MetadataSchema, OwnerTransfer, OwnerTransferRecord,
+    PagedEventsResponse, PostEvent, PostEventProfile, PostEventSession, PostTicketTier, Ticket,
+    TicketPayment, TicketPaymentMethod, UpdateEvent,
+};
+use shared::icrc_models::Account;
 
 // This method is used to add a event to the canister,
 // The method is async because it optionally creates a new canister
@@ -18,7 +26,7 @@ async fn add_event(
     group_identifier: Principal,
     member_identifier: Principal,
     event_attendee_canister: Principal,
-) -> Result<EventResponse, ApiError> {
+) -> Result<AddEventResponse, ApiError> {
     match Store::can_write(caller(), group_identifier, member_identifier).await {
         Ok(_caller) => {
             Store::add_event(_caller, value, group_identifier, event_attendee_canister).await
@@ -27,6 +35,24 @@ async fn add_event(
     }
 }
 
+// This method is used by a sibling canister that was at capacity to finalize a forwarded event,
+// the pending event is only made visible after the owner is added as an attendee
+// can only be called by the sibling canister that forwarded the event
+#[update(guard = "auth")]
+async fn finalize_forwarded_event(
+    identifier: Principal,
+    event_attendee_canister: Principal,
+) -> Result<EventResponse, ApiError> {
+    Store::finalize_forwarded_event(caller(), identifier, event_attendee_canister).await
+}
+
+// This method is used by a sibling canister that was at capacity to remove a forwarded event it couldn't finalize
+// can only be called by the sibling canister that forwarded the event
+#[update(guard = "auth")]
+fn remove_forwarded_event(identifier: Principal) -> Result<(), ApiError> {
+    Store::remove_forwarded_event(caller(), identifier)
+}
+
 // This method is used to get an event
 #[query]
 fn get_event(
@@ -46,41 +72,40 @@ fn get_event_privacy_and_owner(
 }
 
 // This method is used to get events filtered and sorted with pagination
+// the events are sorted on the sort keys in order, events that are equal on all the keys are sorted by their identifier
+// when facets are requested they are counted over all the events that matched the filter
 #[query]
 fn get_events(
     limit: usize,
     page: usize,
-    sort: EventSort,
-    filter: Vec<EventFilter>,
-    filter_type: FilterType,
+    sort: Vec<EventSort>,
+    filter: EventFilterExpression,
     group_identifier: Option<Principal>,
-) -> Result<PagedResponse<EventResponse>, ApiError> {
+    facets: Option<EventFacetRequest>,
+) -> Result<PagedEventsResponse, ApiError> {
     Ok(Store::get_events(
         limit,
         page,
         sort,
         filter,
-        filter_type,
         group_identifier,
+        facets,
     ))
 }
 
 // COMPOSITE_QUERY PREPARATION
 // This methods is used by the parent canister to get filtered events the (this) child canister
-// Data serialized and send as byte array chunks ` (bytes, (start_chunk, end_chunk)) `
-// The parent canister can then deserialize the data and pass it to the frontend
-#[query]
+// The first `max_entries` events in the requested order are returned with the total amount of filtered events
+// Data serialized and send as byte array chunks, the first call creates a snapshot that needs to be passed along with the next chunk calls
+// The parent canister can then verify and deserialize the data and merge it with the data of the other child canisters
+#[query(guard = "is_parent")]
 fn get_chunked_data(
-    filters: Vec<EventFilter>,
-    filter_type: FilterType,
+    request: ChunkedDataRequest,
+    snapshot: Option<ChunkSnapshot>,
     chunk: usize,
     max_bytes_per_chunk: usize,
-) -> (Vec<u8>, (usize, usize)) {
-    if caller() != STABLE_DATA.with(|data| data.borrow().get().parent) {
-        return (vec![], (0, 0));
-    }
-
-    Store::get_chunked_data(filters, filter_type, chunk, max_bytes_per_chunk)
+) -> Result<ChunkedData, ApiError> {
+    Store::get_chunked_data(request, snapshot, chunk, max_bytes_per_chunk)
 }
 
 // This method is used to get the amount of events for a list of groups
@@ -89,29 +114,86 @@ fn get_events_count(group_identifiers: Vec<Principal>) -> Vec<(Principal, usize)
     Store::get_events_count(group_identifiers)
 }
 
+// This method is used to get the amount of upcoming, past and cancelled events for a list of groups
+#[query]
+fn get_events_count_breakdown(group_identifiers: Vec<Principal>) -> Vec<(Principal, EventsCount)> {
+    Store::get_events_count_breakdown(group_identifiers)
+}
+
+// This method is used to get the amount of live (not deleted or cancelled) events per tag for a group
+#[query]
+fn get_tag_counts(group_identifier: Principal) -> Vec<(u32, usize)> {
+    Store::get_tag_counts(group_identifier)
+}
+
+// This method is used by the parent canister to get the groups that have events stored on this canister
+#[query(guard = "is_parent")]
+fn get_group_identifiers() -> Vec<Principal> {
+    Store::get_group_identifiers()
+}
+
 // This method is used to update an existing event
+// when the event changed since the expected version a conflict with the current event is returned
+// the dates can't be changed when sessions of the event fall outside the new dates
 #[update(guard = "auth")]
 async fn edit_event(
     identifier: Principal,
     value: UpdateEvent,
+    expected_version: u64,
     group_identifier: Principal,
     member_identifier: Principal,
-    event_attendee_canister: Principal,
-) -> Result<EventResponse, ApiError> {
-    match Store::can_edit(caller(), identifier, group_identifier, member_identifier).await {
-        Ok(_caller) => Store::edit_event(identifier, value, event_attendee_canister).await,
-        Err(err) => Err(err),
+) -> Result<EventResponse, EditEventError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::edit_event(identifier, value, expected_version, group_identifier),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// This method is used to change only the supplied fields of an event
+// only the supplied fields are validated, a conflict is returned when the event changed since the expected version
+// the metadata is cleared by supplying it as `Some(None)`
+// the dates can't be changed when sessions of the event fall outside the new dates
+#[update(guard = "auth")]
+async fn patch_event(
+    identifier: Principal,
+    patch: EventPatch,
+    expected_version: u64,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, EditEventError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::patch_event(identifier, patch, expected_version, group_identifier),
+        Err(err) => Err(err.into()),
     }
 }
 
 // This method is used to delete an existing event
 #[update(guard = "auth")]
-fn delete_event(
+async fn delete_event(
     identifier: Principal,
     group_identifier: Principal,
-    _member_identifier: Principal,
+    member_identifier: Principal,
 ) -> Result<(), ApiError> {
-    Store::delete_event(identifier, group_identifier)
+    match Store::can_delete(caller(), identifier, group_identifier, member_identifier).await {
+        Ok(_caller) => Store::delete_event(identifier, group_identifier),
+        Err(err) => Err(err),
+    }
 }
 
 // This method is used to cancel an event
@@ -122,8 +204,21 @@ async fn cancel_event(
     group_identifier: Principal,
     member_identifier: Principal,
 ) -> Result<(), ApiError> {
-    match Store::can_edit(caller(), identifier, group_identifier, member_identifier).await {
-        Ok(_caller) => Store::cancel_event(identifier, reason, group_identifier),
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::Cancel),
+    )
+    .await
+    {
+        Ok(_caller) => {
+            Store::cancel_event(identifier, reason, group_identifier)?;
+            // Failed refunds are stored on the payment and can be retried with `refund_ticket_payments`
+            let _ = Store::refund_ticket_payments(identifier, group_identifier).await;
+            Ok(())
+        }
         Err(err) => Err(err),
     }
 }
@@ -146,6 +241,677 @@ pub fn update_attendee_count_on_event(
     return Err(false);
 }
 
+// This method is used by the parent canister to push the configured event attendee canister
+// the ticket holders are recorded on it, when it isnt pushed yet it is fetched from the parent canister
+#[update(guard = "is_parent")]
+fn set_event_attendee_canister_by_parent(event_attendee_canister: Principal) {
+    Store::store_event_attendee_canister(event_attendee_canister)
+}
+
+// BULK OPERATIONS
+// The permission is checked once for the group, the owner and hosts with the matching ability are always allowed
+// every event gets its own result, a failing event doesnt stop the other events from being updated
+
+// This method is used to cancel multiple events of a group
+#[update(guard = "auth")]
+async fn bulk_cancel_events(
+    identifiers: Vec<Principal>,
+    reason: String,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Vec<(Principal, Result<(), ApiError>)> {
+    let results = Store::bulk_cancel_events(
+        caller(),
+        identifiers,
+        reason,
+        group_identifier,
+        member_identifier,
+    )
+    .await;
+
+    // Failed refunds are stored on the payment and can be retried with `refund_ticket_payments`
+    for (identifier, result) in results.iter() {
+        if result.is_ok() {
+            let _ = Store::refund_ticket_payments(*identifier, group_identifier).await;
+        }
+    }
+    results
+}
+
+// This method is used to delete multiple events of a group
+#[update(guard = "auth")]
+async fn bulk_delete_events(
+    identifiers: Vec<Principal>,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Vec<(Principal, Result<(), ApiError>)> {
+    Store::bulk_delete_events(caller(), identifiers, group_identifier, member_identifier).await
+}
+
+// This method is used to add and remove tags on multiple events of a group
+#[update(guard = "auth")]
+async fn bulk_update_tags(
+    identifiers: Vec<Principal>,
+    add_tags: Vec<u32>,
+    remove_tags: Vec<u32>,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Vec<(Principal, Result<(), ApiError>)> {
+    Store::bulk_update_tags(
+        caller(),
+        identifiers,
+        add_tags,
+        remove_tags,
+        group_identifier,
+        member_identifier,
+    )
+    .await
+}
+
+// This method is used to propose an ownership transfer for multiple events of a group
+// the new owner needs to accept the transfer for every event
+#[update(guard = "auth")]
+async fn bulk_transfer_ownership(
+    identifiers: Vec<Principal>,
+    new_owner: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Vec<(Principal, Result<(), ApiError>)> {
+    Store::bulk_transfer_ownership(
+        caller(),
+        identifiers,
+        new_owner,
+        group_identifier,
+        member_identifier,
+    )
+    .await
+}
+
+// EVENT HOSTS
+// Co-hosts and moderators can edit, cancel or check in attendees of an event based on their abilities
+// only the owner and group members with the edit permission can manage the hosts
+
+// This method is used to add a host to an event, an existing host is replaced
+#[update(guard = "auth")]
+async fn add_event_host(
+    identifier: Principal,
+    host: Principal,
+    role: EventHostRole,
+    abilities: Vec<EventHostAbility>,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        None,
+    )
+    .await
+    {
+        Ok(_caller) => {
+            Store::add_event_host(_caller, identifier, host, role, abilities, group_identifier)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to remove a host from an event
+#[update(guard = "auth")]
+async fn remove_event_host(
+    identifier: Principal,
+    host: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        None,
+    )
+    .await
+    {
+        Ok(_caller) => Store::remove_event_host(identifier, host, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to check if a principal is the owner or a host of the event with the ability (inter-canister call)
+#[query]
+fn has_event_host_ability(
+    identifier: Principal,
+    principal: Principal,
+    ability: EventHostAbility,
+) -> bool {
+    Store::has_event_host_ability(identifier, principal, ability)
+}
+
+// TICKETS
+// Ticket tiers are managed by the owner and hosts that can edit the details, the supply and sale window are checked on every claim or purchase
+// the holders of the tickets are recorded on the event attendee canister
+
+// This method is used to add a ticket tier with a price, supply and sale window to an event
+#[update(guard = "auth")]
+async fn add_ticket_tier(
+    identifier: Principal,
+    tier: PostTicketTier,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::add_ticket_tier(identifier, tier, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to update a ticket tier, the price can only be changed before tickets are sold
+#[update(guard = "auth")]
+async fn update_ticket_tier(
+    identifier: Principal,
+    tier_id: u32,
+    tier: PostTicketTier,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::update_ticket_tier(identifier, tier_id, tier, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to remove a ticket tier without sold tickets
+#[update(guard = "auth")]
+async fn remove_ticket_tier(
+    identifier: Principal,
+    tier_id: u32,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::remove_ticket_tier(identifier, tier_id, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to claim a ticket of a free tier, the holder is recorded on the event attendee canister
+#[update(guard = "auth")]
+async fn claim_ticket(
+    identifier: Principal,
+    tier_id: u32,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<Ticket, ApiError> {
+    match Store::can_read(caller(), group_identifier, member_identifier).await {
+        Ok(_caller) => Store::claim_ticket(_caller, identifier, tier_id, group_identifier).await,
+        Err(err) => Err(err),
+    }
+}
+
+// TICKET PAYMENTS
+// Paid tickets are collected from the buyer into the subaccount of the event, the payments are refunded when the event is canceled
+
+// This method is used to buy a ticket, the payment is collected with an ICRC-2 approval or an ICRC-1 transfer
+// before the holder is recorded on the event attendee canister, the payment is refunded when recording the holder fails
+#[update(guard = "auth")]
+async fn buy_ticket(
+    identifier: Principal,
+    tier_id: u32,
+    payment_method: TicketPaymentMethod,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<Ticket, ApiError> {
+    match Store::can_read(caller(), group_identifier, member_identifier).await {
+        Ok(_caller) => {
+            Store::buy_ticket(
+                _caller,
+                identifier,
+                tier_id,
+                payment_method,
+                group_identifier,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to get the account the caller transfers the ticket price (plus fee) to when paying with an ICRC-1 transfer
+// what is transferred more than the price (plus fee) is returned to the caller once the ticket is paid
+#[query]
+fn get_ticket_payment_account(identifier: Principal) -> Account {
+    Store::get_ticket_payment_account(identifier, caller())
+}
+
+// This method is used to get the collected ticket payments of an event
+#[update(guard = "auth")]
+async fn get_ticket_payments(
+    identifier: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<Vec<TicketPayment>, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        None,
+    )
+    .await
+    {
+        Ok(_caller) => Ok(Store::get_ticket_payments(identifier)),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to retry the refunds of a canceled event, refunds are started when the event is canceled
+// interrupted purchases and refunds are resubmitted to the ledger, which deduplicates them within its transaction window (24 hours)
+// the payment of an interrupted purchase is refunded because the holder wasnt recorded
+#[update(guard = "auth")]
+async fn refund_ticket_payments(
+    identifier: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<Vec<TicketPayment>, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::Cancel),
+    )
+    .await
+    {
+        Ok(_caller) => Store::refund_ticket_payments(identifier, group_identifier).await,
+        Err(err) => Err(err),
+    }
+}
+
+// SESSIONS
+// The agenda of an event consists of sessions that fall within the dates of the event
+
+// This method is used to add a session to the agenda of an event, the session needs to fall within the dates of the event
+#[update(guard = "auth")]
+async fn add_event_session(
+    identifier: Principal,
+    session: PostEventSession,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::add_event_session(identifier, session, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to update a session of an event
+#[update(guard = "auth")]
+async fn update_event_session(
+    identifier: Principal,
+    session_id: u32,
+    session: PostEventSession,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => {
+            Store::update_event_session(identifier, session_id, session, group_identifier)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to remove a session from an event
+#[update(guard = "auth")]
+async fn remove_event_session(
+    identifier: Principal,
+    session_id: u32,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::remove_event_session(identifier, session_id, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to export a session of an event as an iCalendar (.ics) file
+#[query]
+fn get_event_session_ics(
+    identifier: Principal,
+    session_id: u32,
+    group_identifier: Principal,
+) -> Result<String, ApiError> {
+    Store::get_event_session_ics(identifier, session_id, group_identifier)
+}
+
+// SPEAKERS AND SPONSORS
+// Speakers and sponsors are shown on the event, a profile can optionally be linked to a principal
+
+// This method is used to add a speaker or sponsor to an event
+#[update(guard = "auth")]
+async fn add_event_profile(
+    identifier: Principal,
+    kind: EventProfileKind,
+    profile: PostEventProfile,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::add_event_profile(identifier, kind, profile, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to update a speaker or sponsor of an event
+#[update(guard = "auth")]
+async fn update_event_profile(
+    identifier: Principal,
+    kind: EventProfileKind,
+    profile_id: u32,
+    profile: PostEventProfile,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => {
+            Store::update_event_profile(identifier, kind, profile_id, profile, group_identifier)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to remove a speaker or sponsor from an event
+#[update(guard = "auth")]
+async fn remove_event_profile(
+    identifier: Principal,
+    kind: EventProfileKind,
+    profile_id: u32,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::EditDetails),
+    )
+    .await
+    {
+        Ok(_caller) => Store::remove_event_profile(identifier, kind, profile_id, group_identifier),
+        Err(err) => Err(err),
+    }
+}
+
+// CHECK-IN
+// Attendees of physical events show a signed check-in code at the door which is checked in by the owner or a host
+
+// This method is used to get the signed check-in code of the caller for a physical event, shown as a QR code at the door
+// only attendees and ticket holders of the event get a check-in code
+#[update(guard = "auth")]
+async fn get_check_in_code(
+    identifier: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<String, ApiError> {
+    match Store::can_read(caller(), group_identifier, member_identifier).await {
+        Ok(_caller) => Store::get_check_in_code(_caller, identifier, group_identifier).await,
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used by the owner and hosts to check in an attendee with their check-in code
+#[update(guard = "auth")]
+async fn check_in(
+    identifier: Principal,
+    code: String,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<CheckIn, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        Some(EventHostAbility::CheckInAttendees),
+    )
+    .await
+    {
+        Ok(_caller) => Store::check_in(_caller, identifier, code, group_identifier).await,
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used to get the amount of registered attendees and the amount of attendees that checked in
+#[query]
+fn get_check_in_stats(
+    identifier: Principal,
+    group_identifier: Principal,
+) -> Result<CheckInStats, ApiError> {
+    Store::get_check_in_stats(identifier, group_identifier)
+}
+
+// OWNERSHIP TRANSFER
+// The ownership of an event is transferred in two steps, an editor proposes the new owner and the new owner accepts it
+
+// This method is used to propose a new owner for an event, the proposal expires after 7 days
+#[update(guard = "auth")]
+async fn propose_owner_transfer(
+    identifier: Principal,
+    new_owner: Principal,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<OwnerTransfer, ApiError> {
+    match Store::can_edit(
+        caller(),
+        identifier,
+        group_identifier,
+        member_identifier,
+        None,
+    )
+    .await
+    {
+        Ok(_caller) => {
+            Store::propose_owner_transfer(_caller, identifier, new_owner, group_identifier)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used by the proposed owner to accept the ownership transfer of an event
+#[update(guard = "auth")]
+async fn accept_owner_transfer(
+    identifier: Principal,
+    group_identifier: Principal,
+) -> Result<EventResponse, ApiError> {
+    Store::accept_owner_transfer(caller(), identifier, group_identifier).await
+}
+
+// This method is used to get the pending ownership transfer and the ownership transfer history of an event
+#[query]
+fn get_owner_transfers(
+    identifier: Principal,
+    group_identifier: Principal,
+) -> Result<(Option<OwnerTransfer>, Vec<OwnerTransferRecord>), ApiError> {
+    Store::get_owner_transfers(identifier, group_identifier)
+}
+
+// METADATA SCHEMA
+// The typed metadata of events is validated against the schema of the group, the schemas are kept in sync by the parent canister
+
+// This method is used to register the metadata schema of a group, the typed metadata of events is validated against it
+// the schema is registered on the parent canister which pushes it to all the child canisters
+#[update(guard = "auth")]
+async fn set_metadata_schema(
+    fields: Vec<MetadataField>,
+    group_identifier: Principal,
+    member_identifier: Principal,
+) -> Result<MetadataSchema, ApiError> {
+    match Store::can_manage_metadata_schema(caller(), group_identifier, member_identifier).await {
+        Ok(_caller) => Store::set_metadata_schema(_caller, group_identifier, fields).await,
+        Err(err) => Err(err),
+    }
+}
+
+// This method is used by the parent canister to push the metadata schema of a group
+#[update(guard = "is_parent")]
+fn set_metadata_schema_by_parent(schema: MetadataSchema) {
+    Store::store_metadata_schema(schema)
+}
+
+// This method is used to get the metadata schema of a group
+#[query]
+fn get_metadata_schema(group_identifier: Principal) -> Option<MetadataSchema> {
+    Store::get_metadata_schema(group_identifier)
+}
+
+// ROLE CACHE
+// The roles of the group and member canisters are cached, the cache is invalidated by those canisters when the roles change
+
+// This method is used by the group and member canisters to remove cached roles when they changed (inter-canister call)
+// without a member the group roles and the roles of all the members of the group are removed
+#[update]
+fn invalidate_role_cache(
+    group_identifier: Principal,
+    member_identifier: Option<Principal>,
+) -> Result<(), ApiError> {
+    Store::invalidate_role_cache(caller(), group_identifier, member_identifier)
+}
+
+// EVENT MIGRATION
+// These methods are used by the parent canister to move events between child canisters
+// the events are copied to the target canister, verified by the parent and then removed from the source canister
+// the check-ins of the events are moved once the events are removed, so no check-in is missed in the meantime
+
+// This method is used to get the identifiers of all the events of a group
+#[query(guard = "is_parent")]
+fn get_migration_identifiers(group_identifier: Principal) -> Vec<Principal> {
+    Store::get_migration_identifiers(group_identifier)
+}
+
+// This method is used to get the stored events for a list of identifiers
+#[query(guard = "is_parent")]
+fn get_migration_entries(identifiers: Vec<Principal>) -> Vec<(Principal, Event)> {
+    Store::get_migration_entries(identifiers)
+}
+
+// This method is used to get the identifiers of the events that can't be moved because they hold ticket payments
+#[query(guard = "is_parent")]
+fn get_migration_blocked_identifiers(identifiers: Vec<Principal>) -> Vec<Principal> {
+    Store::get_migration_blocked_identifiers(identifiers)
+}
+
+// This method is used to store events moved from a sibling canister
+#[update(guard = "is_parent")]
+fn import_migration_entries(entries: Vec<(Principal, Event)>) -> Result<(), ApiError> {
+    Store::import_migration_entries(entries)
+}
+
+// This method is used to remove events that are moved to a sibling canister
+// entries are passed with their fingerprint, events that changed in the meantime or hold ticket payments are not removed
+#[update(guard = "is_parent")]
+fn remove_migration_entries(entries: Vec<(Principal, String)>) -> Vec<Principal> {
+    Store::remove_migration_entries(entries)
+}
+
+// This method is used to remove the copied events that are not removed from the source canister
+#[update(guard = "is_parent")]
+fn discard_migration_entries(identifiers: Vec<Principal>) {
+    Store::discard_migration_entries(identifiers)
+}
+
+// This method is used to get the check-ins of events that are moved to a sibling canister
+#[query(guard = "is_parent")]
+fn get_migration_check_ins(identifiers: Vec<Principal>) -> Vec<CheckIn> {
+    Store::get_migration_check_ins(identifiers)
+}
+
+// This method is used to store the check-ins of events moved from a sibling canister
+#[update(guard = "is_parent")]
+fn import_migration_check_ins(check_ins: Vec<CheckIn>) {
+    Store::import_migration_check_ins(check_ins)
+}
+
+// This method is used to remove the check-ins of events that are removed from this canister by a migration
+#[update(guard = "is_parent")]
+fn remove_migration_check_ins(identifiers: Vec<Principal>) {
+    Store::remove_migration_check_ins(identifiers)
+}
+
+pub fn is_parent() -> Result<(), String> {
+    match caller() == STABLE_DATA.with(|data| data.borrow().get().parent) {
+        true => Ok(()),
+        false => Err("Unauthorized".to_string()),
+    }
+}
+
 pub fn auth() -> Result<(), String> {
     match caller() == Principal::anonymous() {
         true => Err("Unauthorized".to_string()),
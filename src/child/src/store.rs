@@ -1,34 +1,58 @@
-use candid::Principal;
+use candid::{decode_one, encode_one, CandidType, Principal};
 use ic_cdk::{
     api::{call, time},
-    caller,
+    id,
 };
 use ic_scalable_canister::ic_scalable_misc::{
     enums::{
         api_error_type::{ApiError, ApiErrorType},
-        filter_type::FilterType,
+        location_type::Location,
         privacy_type::Privacy,
-        sort_type::SortDirection,
     },
     helpers::{
         error_helper::api_error,
         paging_helper::get_paged_data,
         role_helper::{default_roles, get_group_roles, get_member_roles, has_permission},
-        serialize_helper::serialize,
+        serialize_helper::{deserialize, serialize},
     },
     models::{
         identifier_model::Identifier,
-        paged_response_models::PagedResponse,
         permissions_models::{PermissionActionType, PermissionType},
     },
 };
 use ic_scalable_canister::store::Data;
 
-use shared::event_models::{Event, EventFilter, EventResponse, EventSort, PostEvent, UpdateEvent};
-
-use std::{cell::RefCell, collections::HashMap, iter::FromIterator};
+use shared::chunk_models::{ChunkSnapshot, ChunkedData, ChunkedDataRequest};
+use shared::event_facets::{EventFacetCounter, EventFacetRequest, EventFacets};
+use shared::event_models::{
+    AddEventResponse, CheckIn, CheckInStats, EditEventError, Event, EventFilterExpression,
+    EventHost, EventHostAbility, EventHostRole, EventPatch, EventProfile, EventProfileKind,
+    EventResponse, EventSession, EventSort, EventsCount, EventsCountBreakdown, EventsSlice,
+    MetadataField, MetadataSchema, OwnerTransfer, OwnerTransferAction, OwnerTransferRecord,
+    PagedEventsResponse, PostEvent, PostEventProfile, PostEventSession, PostTicketTier, Ticket,
+    TicketPayment, TicketPaymentMethod, TicketPaymentStatus, TicketPrice, TicketTier, UpdateEvent,
+};
+use shared::event_sort::sort_events;
+use shared::icrc_models::{ticket_payment_memo, ticket_payment_subaccount, Account};
+
+use serde::de::DeserializeOwned;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    future::Future,
+    iter::FromIterator,
+};
 
-use crate::{validate::validate_post_event, validate::validate_update_event, IDENTIFIER_KIND};
+use crate::{
+    check_in::{CheckInCode, CheckInCodeError},
+    ledger::Ledger,
+    validate::{
+        validate_event_patch, validate_event_profile, validate_event_session,
+        validate_event_sessions_date, validate_metadata_schema, validate_post_event,
+        validate_ticket_tier, validate_update_event,
+    },
+    IDENTIFIER_KIND,
+};
 
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
@@ -39,6 +63,56 @@ type Memory = VirtualMemory<DefaultMemoryImpl>;
 
 pub static DATA_MEMORY_ID: MemoryId = MemoryId::new(0);
 pub static ENTRIES_MEMORY_ID: MemoryId = MemoryId::new(1);
+pub static METADATA_SCHEMAS_MEMORY_ID: MemoryId = MemoryId::new(2);
+pub static TICKET_PAYMENTS_MEMORY_ID: MemoryId = MemoryId::new(3);
+pub static CHECK_IN_SECRET_MEMORY_ID: MemoryId = MemoryId::new(4);
+pub static CHECK_INS_MEMORY_ID: MemoryId = MemoryId::new(5);
+pub static EVENT_ATTENDEE_CANISTER_MEMORY_ID: MemoryId = MemoryId::new(6);
+
+// The time a chunk snapshot can be used after it is created (5 minutes)
+pub static CHUNK_SNAPSHOT_TTL: u64 = 5 * 60 * 1_000_000_000;
+// The time a proposed ownership transfer can be accepted (7 days)
+pub static OWNER_TRANSFER_TTL: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+// The time the group and member roles are cached (5 minutes)
+pub static ROLE_CACHE_TTL: u64 = 5 * 60 * 1_000_000_000;
+// The maximum amount of cached group and member roles, the roles that expire first are removed when it is reached
+pub static MAX_ROLE_CACHE_ENTRIES: usize = 10_000;
+// The maximum amount of ticket tiers per event
+pub static MAX_TICKET_TIERS: usize = 20;
+// The maximum amount of sessions per event
+pub static MAX_EVENT_SESSIONS: usize = 200;
+// The maximum amount of speakers and of sponsors per event
+pub static MAX_EVENT_PROFILES: usize = 100;
+// The time a check-in code can be used after the start of an event without an end date (24 hours)
+pub static CHECK_IN_CODE_TTL: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// The role lookups that are cached, the roles are stored candid encoded
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum RoleCacheKey {
+    Group(Principal),
+    // (member_identifier, group_identifier)
+    Member(Principal, Principal),
+}
+
+// Marks a ticket payment as in progress while its ledger calls are awaited
+// the mark is removed when the guard is dropped, which also happens when a callback traps
+pub struct TicketPaymentGuard(String);
+
+impl TicketPaymentGuard {
+    // Returns `None` when the payment is already in progress
+    pub fn new(key: String) -> Option<Self> {
+        match TICKET_PAYMENTS_IN_PROGRESS.with(|keys| keys.borrow_mut().insert(key.clone())) {
+            true => Some(Self(key)),
+            false => None,
+        }
+    }
+}
+
+impl Drop for TicketPaymentGuard {
+    fn drop(&mut self) {
+        TICKET_PAYMENTS_IN_PROGRESS.with(|keys| keys.borrow_mut().remove(&self.0));
+    }
+}
 
 thread_local! {
         pub static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
@@ -58,19 +132,76 @@ thread_local! {
                 MEMORY_MANAGER.with(|m| m.borrow().get(ENTRIES_MEMORY_ID)),
             )
         );
+
+        // The metadata schemas of the groups, the parent canister pushes the schemas to all the child canisters
+        pub static METADATA_SCHEMAS: RefCell<StableBTreeMap<String, MetadataSchema, Memory>> = RefCell::new(
+            StableBTreeMap::init(
+                MEMORY_MANAGER.with(|m| m.borrow().get(METADATA_SCHEMAS_MEMORY_ID)),
+            )
+        );
+
+        // The collected ticket payments, the key is prefixed with the event identifier
+        pub static TICKET_PAYMENTS: RefCell<StableBTreeMap<String, TicketPayment, Memory>> = RefCell::new(
+            StableBTreeMap::init(
+                MEMORY_MANAGER.with(|m| m.borrow().get(TICKET_PAYMENTS_MEMORY_ID)),
+            )
+        );
+
+        // The secret the check-in codes are signed with, shared by the child canisters through the parent canister (empty when not fetched yet)
+        pub static CHECK_IN_SECRET: RefCell<StableCell<Vec<u8>, Memory>> = RefCell::new(
+            StableCell::init(
+                MEMORY_MANAGER.with(|m| m.borrow().get(CHECK_IN_SECRET_MEMORY_ID)),
+                vec![],
+            ).expect("failed")
+        );
+
+        // The check-ins of the attendees, the key is prefixed with the event identifier
+        pub static CHECK_INS: RefCell<StableBTreeMap<String, CheckIn, Memory>> = RefCell::new(
+            StableBTreeMap::init(
+                MEMORY_MANAGER.with(|m| m.borrow().get(CHECK_INS_MEMORY_ID)),
+            )
+        );
+
+        // The event attendee canister configured on the parent canister, stored as principal bytes (empty when not fetched yet)
+        pub static EVENT_ATTENDEE_CANISTER: RefCell<StableCell<Vec<u8>, Memory>> = RefCell::new(
+            StableCell::init(
+                MEMORY_MANAGER.with(|m| m.borrow().get(EVENT_ATTENDEE_CANISTER_MEMORY_ID)),
+                vec![],
+            ).expect("failed")
+        );
+
+        // The keys of the ticket payments of which the ledger calls are awaited (heap)
+        pub static TICKET_PAYMENTS_IN_PROGRESS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+
+        // The groups this canister is registered for on the parent canister (heap, registration is repeated after an upgrade)
+        pub static REGISTERED_GROUPS: RefCell<HashSet<Principal>> = RefCell::new(HashSet::new());
+
+        // The time of the last write to the entries, used as the version of the chunk snapshots (heap)
+        pub static DATA_VERSION: RefCell<u64> = const { RefCell::new(0) };
+
+        // The cached group and member roles with the time they expire (heap)
+        pub static ROLE_CACHE: RefCell<HashMap<RoleCacheKey, (u64, Vec<u8>)>> = RefCell::new(HashMap::new());
+
+        // The generation of the cached roles, increased on invalidation so roles fetched before it aren't cached (heap)
+        pub static ROLE_CACHE_GENERATIONS: RefCell<HashMap<RoleCacheKey, u64>> = RefCell::new(HashMap::new());
 }
 
 pub struct Store;
 
 impl Store {
     // This method is used to store a new event
+    // The event is stored as pending and only becomes visible after the owner is added as an attendee,
+    // when the canister is at capacity the event is forwarded to a new sibling canister that finalizes it
     pub async fn add_event(
         caller: Principal,
         post_event: PostEvent,
         group_identifier: Principal,
         event_attendee_canister: Principal,
-    ) -> Result<EventResponse, ApiError> {
-        match validate_post_event(post_event.clone()) {
+    ) -> Result<AddEventResponse, ApiError> {
+        match validate_post_event(
+            post_event.clone(),
+            Self::get_metadata_schema(group_identifier).as_ref(),
+        ) {
             Err(err) => Err(err),
             Ok(_) => {
                 // Create a new event with the post_event data
@@ -90,14 +221,23 @@ impl Store {
                     attendee_count: HashMap::from_iter(vec![(event_attendee_canister, 1)]),
                     is_canceled: (false, "".to_string()),
                     is_deleted: false,
+                    is_pending: true,
                     updated_on: time(),
                     created_on: time(),
                     group_identifier,
                     metadata: post_event.metadata,
+                    typed_metadata: post_event.typed_metadata,
+                    owner_transfer: None,
+                    owner_transfer_history: vec![],
+                    hosts: vec![],
+                    version: 0,
+                    ticket_tiers: vec![],
+                    sessions: vec![],
+                    speakers: vec![],
+                    sponsors: vec![],
+                    forwarded_by: None,
                 };
 
-                // TODO: Validate the event data
-
                 match STABLE_DATA.with(|data| {
                     ENTRIES.with(|entries| {
                         Data::add_entry(
@@ -110,110 +250,329 @@ impl Store {
                 }) {
                     // If the canister is at capacity, we spawn a new canister
                     Err(err) => match err {
-                        ApiError::CanisterAtCapacity(message) => {
-                            let _data = STABLE_DATA.with(|d| d.borrow().get().clone());
-                            // Spawn a sibling canister and pass the event data to it
-                            match Data::spawn_sibling(&_data, new_event).await {
-                                Ok(_) => Err(ApiError::CanisterAtCapacity(message)),
-                                Err(err) => Err(err),
-                            }
+                        ApiError::CanisterAtCapacity(_) => {
+                            Self::forward_event_to_sibling(new_event, event_attendee_canister).await
                         }
                         _ => Err(err),
                     },
-                    // If the event is stored successfully, we add the owner as an attendee on the event_attendee canister (inter-canister call)
-                    Ok((_identifier, event)) => {
-                        let add_attendee_result = Self::add_owner_as_attendee(
-                            &event.owner,
-                            &_identifier,
-                            &group_identifier,
-                            &event_attendee_canister,
-                        )
-                        .await;
-
-                        // If the attendee is added successfully, we return the event response
-                        match add_attendee_result {
-                            Ok(_) => {
-                                Ok(Self::map_to_event_response(_identifier.to_string(), event))
-                            }
-                            Err(_) => {
-                                ENTRIES.with(|entries| Data::remove_entry(entries, &_identifier));
-                                return Err(api_error(
-                                    ApiErrorType::Unauthorized,
-                                    "ATTENDEE_ADD_FAILED",
-                                    "Storing the attendee failed",
-                                    STABLE_DATA
-                                        .with(|data| Data::get_name(data.borrow().get()))
-                                        .as_str(),
-                                    "add_event",
-                                    None,
-                                ));
-                            }
-                        }
+                    // If the event is stored successfully, we add the owner as an attendee and make the event visible
+                    Ok((_identifier, _)) => {
+                        let event =
+                            Self::finalize_pending_event(_identifier, event_attendee_canister)
+                                .await?;
+                        Ok(AddEventResponse {
+                            canister: id(),
+                            event,
+                        })
                     }
                 }
             }
         }
     }
 
-    // This method is used to edit an event
-    pub async fn edit_event(
+    // Spawn a sibling canister, pass the pending event to it and let the sibling finalize the event (inter-canister call)
+    // the parent returns the identifier of the pending event on the sibling, which contains the principal of the sibling
+    // the pending event is removed from the sibling when it can't be finalized
+    async fn forward_event_to_sibling(
+        event: Event,
+        event_attendee_canister: Principal,
+    ) -> Result<AddEventResponse, ApiError> {
+        let _data = STABLE_DATA.with(|d| d.borrow().get().clone());
+        let forwarded_identifier = Data::spawn_sibling(&_data, event).await?;
+        let (_, sibling, _) = Identifier::decode(&forwarded_identifier);
+
+        let result: Result<(Result<EventResponse, ApiError>,), _> = call::call(
+            sibling,
+            "finalize_forwarded_event",
+            (forwarded_identifier, event_attendee_canister),
+        )
+        .await;
+
+        match result {
+            Ok((Ok(event),)) => Ok(AddEventResponse {
+                canister: sibling,
+                event,
+            }),
+            Ok((Err(err),)) => {
+                Self::remove_event_from_sibling(sibling, forwarded_identifier).await;
+                Err(err)
+            }
+            Err(err) => {
+                Self::remove_event_from_sibling(sibling, forwarded_identifier).await;
+                Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "FORWARD_FAILED",
+                    err.1.as_str(),
+                    STABLE_DATA
+                        .with(|data| Data::get_name(data.borrow().get()))
+                        .as_str(),
+                    "add_event",
+                    Some(vec![format!("sibling - {}", &sibling.to_string())]),
+                ))
+            }
+        }
+    }
+
+    // Remove the pending event that is forwarded to the sibling canister (inter-canister call)
+    async fn remove_event_from_sibling(sibling: Principal, forwarded_identifier: Principal) {
+        let result: Result<(Result<(), ApiError>,), _> =
+            call::call(sibling, "remove_forwarded_event", (forwarded_identifier,)).await;
+
+        if let Err(err) = result {
+            println!("{:?}", err.1);
+        }
+    }
+
+    // This method is used to store an event that is passed along by the parent canister when a sibling canister is at capacity
+    // the forwarding canister is stored on the event, only that canister can finalize or remove the pending event
+    // the identifier of the stored event is returned
+    pub fn add_entry_by_parent(
+        entry: Vec<u8>,
+        forwarded_by: Option<Principal>,
+    ) -> Result<Principal, ApiError> {
+        let name = STABLE_DATA.with(|data| Data::get_name(data.borrow().get()));
+        let mut event = deserialize::<Event>(entry).map_err(|err| {
+            api_error(
+                ApiErrorType::BadRequest,
+                "DESERIALIZE_FAILED",
+                err.to_string().as_str(),
+                name.as_str(),
+                "add_entry_by_parent",
+                None,
+            )
+        })?;
+        event.forwarded_by = forwarded_by;
+
+        let (_identifier, _) = STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| {
+                Data::add_entry(data, entries, event, Some(IDENTIFIER_KIND.to_string()))
+            })
+        })?;
+        Self::update_data_version();
+
+        Ok(_identifier)
+    }
+
+    // This method is used to finalize an event that is forwarded by a sibling canister that was at capacity
+    // the pending event is found by the identifier it got when it was stored for the calling sibling
+    pub async fn finalize_forwarded_event(
+        caller: Principal,
         identifier: Principal,
-        update_event: UpdateEvent,
         event_attendee_canister: Principal,
     ) -> Result<EventResponse, ApiError> {
-        // Get the event from the canister
-        match validate_update_event(update_event.clone()) {
-            Err(err) => Err(err),
-            Ok(_) => {
-                let response = STABLE_DATA.with(|data| {
-                    ENTRIES.with(|entries| match Data::get_entry(data, entries, identifier) {
-                        // If the event is not found, we return an error
-                        Err(err) => Err(err),
-                        // If the event is found, we check if the caller is the owner of the event
-                        Ok((_identifier, mut _existing_event)) => {
-                            _existing_event.name = update_event.name;
-                            _existing_event.description = update_event.description;
-                            _existing_event.date = update_event.date;
-                            _existing_event.privacy = update_event.privacy;
-                            _existing_event.website = update_event.website;
-                            _existing_event.location = update_event.location;
-                            _existing_event.image = update_event.image;
-                            _existing_event.banner_image = update_event.banner_image;
-                            _existing_event.owner = update_event.owner;
-                            _existing_event.metadata = update_event.metadata;
-                            _existing_event.tags = update_event.tags;
-                            _existing_event.updated_on = time();
-
-                            // Update the event
-                            match Data::update_entry(data, entries, _identifier, _existing_event) {
-                                Err(err) => Err(err),
-                                Ok((__identifier, event)) => Ok((
-                                    Ok(Self::map_to_event_response(
-                                        __identifier.to_string(),
-                                        event.clone(),
-                                    )),
-                                    event.group_identifier.clone(),
-                                )),
-                            }
-                        }
-                    })
-                });
+        let (_identifier, mut _event) =
+            Self::get_forwarded_event(caller, identifier, "finalize_forwarded_event")?;
 
-                let _response = response.clone().unwrap();
-                let user_principal = &_response.0.unwrap().owner;
-                let group_identifier = &_response.1;
+        // The event can only be finalized once by the forwarding canister
+        _event.forwarded_by = None;
+        STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
+        })?;
 
-                let _ = Self::add_owner_as_attendee(
-                    &user_principal,
-                    &identifier,
-                    group_identifier,
-                    &event_attendee_canister,
-                )
-                .await;
+        Self::finalize_pending_event(_identifier, event_attendee_canister).await
+    }
+
+    // This method is used to remove a forwarded event that the forwarding sibling canister couldn't finalize
+    // the event is only removed while it is still pending
+    pub fn remove_forwarded_event(
+        caller: Principal,
+        identifier: Principal,
+    ) -> Result<(), ApiError> {
+        let (_identifier, _) =
+            Self::get_forwarded_event(caller, identifier, "remove_forwarded_event")?;
+
+        ENTRIES.with(|entries| Data::remove_entry(entries, &_identifier));
+        Self::update_data_version();
+
+        Ok(())
+    }
 
-                response.unwrap().0
+    // Get the pending event that is forwarded by the sibling canister
+    fn get_forwarded_event(
+        forwarded_by: Principal,
+        identifier: Principal,
+        method_name: &str,
+    ) -> Result<(Principal, Event), ApiError> {
+        let event = ENTRIES.with(|entries| entries.borrow().get(&identifier.to_string()));
+
+        match event {
+            Some(_event) if _event.is_pending && _event.forwarded_by == Some(forwarded_by) => {
+                Ok((identifier, _event))
             }
+            _ => Err(api_error(
+                ApiErrorType::NotFound,
+                "EVENT_NOT_FOUND",
+                "No pending event found that is forwarded by the caller",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                method_name,
+                Some(vec![
+                    format!("caller - {}", &forwarded_by.to_string()),
+                    format!("identifier - {}", &identifier.to_string()),
+                ]),
+            )),
+        }
+    }
+
+    // Add the owner as an attendee and make the pending event visible, the event is removed when this fails
+    async fn finalize_pending_event(
+        identifier: Principal,
+        event_attendee_canister: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let name = STABLE_DATA.with(|data| Data::get_name(data.borrow().get()));
+        let (_, event) = STABLE_DATA
+            .with(|data| ENTRIES.with(|entries| Data::get_entry(data, entries, identifier)))?;
+
+        // The parent needs to know this canister stores events of the group before the event becomes visible
+        if let Err(err) = Self::register_group_on_parent(event.group_identifier).await {
+            ENTRIES.with(|entries| Data::remove_entry(entries, &identifier));
+            return Err(err);
+        }
+
+        let add_attendee_result = Self::add_owner_as_attendee(
+            &event.owner,
+            &identifier,
+            &event.group_identifier,
+            &event_attendee_canister,
+        )
+        .await;
+
+        if add_attendee_result.is_err() {
+            ENTRIES.with(|entries| Data::remove_entry(entries, &identifier));
+            return Err(api_error(
+                ApiErrorType::Unauthorized,
+                "ATTENDEE_ADD_FAILED",
+                "Storing the attendee failed",
+                name.as_str(),
+                "add_event",
+                None,
+            ));
+        }
+
+        // Get the event again, it could be changed during the inter-canister call
+        let (_identifier, mut _event) = STABLE_DATA
+            .with(|data| ENTRIES.with(|entries| Data::get_entry(data, entries, identifier)))?;
+        _event.is_pending = false;
+
+        let (_identifier, _event) = STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
+        })?;
+        Self::update_data_version();
+
+        Ok(Self::map_to_event_response(_identifier.to_string(), _event))
+    }
+
+    // This method is used to edit an event
+    // when the event changed since the expected version a conflict with the current event is returned
+    pub fn edit_event(
+        identifier: Principal,
+        update_event: UpdateEvent,
+        expected_version: u64,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, EditEventError> {
+        // Get the event of the group from the canister
+        let (_identifier, mut _existing_event) =
+            Self::get_group_entry(identifier, group_identifier, "edit_event")?;
+
+        validate_update_event(
+            update_event.clone(),
+            &_existing_event.date,
+            Self::get_metadata_schema(group_identifier).as_ref(),
+        )?;
+        validate_event_sessions_date(&_existing_event.sessions, &update_event.date)?;
+
+        // Check if the event changed since the expected version
+        Self::check_version(&_identifier, &_existing_event, expected_version)?;
+
+        _existing_event.name = update_event.name;
+        _existing_event.description = update_event.description;
+        _existing_event.date = update_event.date;
+        _existing_event.privacy = update_event.privacy;
+        _existing_event.website = update_event.website;
+        _existing_event.location = update_event.location;
+        _existing_event.image = update_event.image;
+        _existing_event.banner_image = update_event.banner_image;
+        _existing_event.metadata = update_event.metadata;
+        _existing_event.typed_metadata = update_event.typed_metadata;
+        _existing_event.tags = update_event.tags;
+        _existing_event.updated_on = time();
+
+        // Update the event
+        Ok(Self::update_event_entry(_identifier, _existing_event)?)
+    }
+
+    // Check if the stored event still has the version the caller expects
+    fn check_version(
+        identifier: &Principal,
+        event: &Event,
+        expected_version: u64,
+    ) -> Result<(), EditEventError> {
+        match event.version == expected_version {
+            true => Ok(()),
+            false => Err(EditEventError::Conflict(Box::new(
+                Self::map_to_event_response(identifier.to_string(), event.clone()),
+            ))),
+        }
+    }
+
+    // This method is used to change only the supplied fields of an event
+    // when the event changed since the expected version a conflict with the current event is returned
+    pub fn patch_event(
+        identifier: Principal,
+        patch: EventPatch,
+        expected_version: u64,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, EditEventError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "patch_event")?;
+
+        validate_event_patch(
+            &patch,
+            &_event.date,
+            Self::get_metadata_schema(group_identifier).as_ref(),
+        )?;
+        if let Some(date) = &patch.date {
+            validate_event_sessions_date(&_event.sessions, date)?;
+        }
+        Self::check_version(&_identifier, &_event, expected_version)?;
+
+        if let Some(name) = patch.name {
+            _event.name = name;
         }
+        if let Some(description) = patch.description {
+            _event.description = description;
+        }
+        if let Some(date) = patch.date {
+            _event.date = date;
+        }
+        if let Some(privacy) = patch.privacy {
+            _event.privacy = privacy;
+        }
+        if let Some(website) = patch.website {
+            _event.website = website;
+        }
+        if let Some(location) = patch.location {
+            _event.location = location;
+        }
+        if let Some(image) = patch.image {
+            _event.image = image;
+        }
+        if let Some(banner_image) = patch.banner_image {
+            _event.banner_image = banner_image;
+        }
+        if let Some(metadata) = patch.metadata {
+            _event.metadata = metadata;
+        }
+        if let Some(typed_metadata) = patch.typed_metadata {
+            _event.typed_metadata = typed_metadata;
+        }
+        if let Some(tags) = patch.tags {
+            _event.tags = tags;
+        }
+        _event.updated_on = time();
+
+        Ok(Self::update_event_entry(_identifier, _event)?)
     }
 
     // This method is used to delete an event
@@ -241,28 +600,19 @@ impl Store {
                     ));
                 }
 
-                if _event.owner != caller() {
-                    return Err(api_error(
-                        ApiErrorType::Unauthorized,
-                        "UNAUTHORIZED",
-                        "Unauthorized",
-                        STABLE_DATA
-                            .with(|data| Data::get_name(data.borrow().get()))
-                            .as_str(),
-                        "delete_event",
-                        None,
-                    ));
-                }
-
                 // Set the is_deleted flag to true
                 _event.is_deleted = true;
+                _event.version += 1;
 
                 // Update the event
                 match STABLE_DATA.with(|data| {
                     ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
                 }) {
                     Err(err) => Err(err),
-                    Ok((_identifier, _event)) => Ok(()),
+                    Ok((_identifier, _event)) => {
+                        Self::update_data_version();
+                        Ok(())
+                    }
                 }
             }
         }
@@ -296,16 +646,1561 @@ impl Store {
                 }
                 // Set the is_canceled flag to true and specify a reason of cancellation
                 _event.is_canceled = (true, reason);
+                _event.version += 1;
 
                 // Update the event
                 match STABLE_DATA.with(|data| {
                     ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
                 }) {
                     Err(err) => Err(err),
-                    Ok((_identifier, _event)) => Ok(()),
+                    Ok((_identifier, _event)) => {
+                        Self::update_data_version();
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    // BULK OPERATIONS
+    // The permission is checked once for the group, the owner and hosts with the matching ability are always allowed
+    // every event gets its own result, a failing event doesnt stop the other events from being updated
+
+    // This method is used to cancel multiple events of a group
+    pub async fn bulk_cancel_events(
+        caller: Principal,
+        identifiers: Vec<Principal>,
+        reason: String,
+        group_identifier: Principal,
+        member_identifier: Principal,
+    ) -> Vec<(Principal, Result<(), ApiError>)> {
+        let has_permission = Self::check_permission(
+            caller,
+            group_identifier,
+            member_identifier,
+            PermissionActionType::Edit,
+        )
+        .await;
+
+        Self::bulk_update_events(
+            identifiers,
+            group_identifier,
+            has_permission,
+            "bulk_cancel_events",
+            |event| {
+                event.owner == caller
+                    || EventHost::has_ability(&event.hosts, &caller, &EventHostAbility::Cancel)
+            },
+            |event| event.is_canceled = (true, reason.clone()),
+        )
+    }
+
+    // This method is used to delete multiple events of a group
+    pub async fn bulk_delete_events(
+        caller: Principal,
+        identifiers: Vec<Principal>,
+        group_identifier: Principal,
+        member_identifier: Principal,
+    ) -> Vec<(Principal, Result<(), ApiError>)> {
+        let has_permission = Self::check_permission(
+            caller,
+            group_identifier,
+            member_identifier,
+            PermissionActionType::Delete,
+        )
+        .await;
+
+        Self::bulk_update_events(
+            identifiers,
+            group_identifier,
+            has_permission,
+            "bulk_delete_events",
+            |event| event.owner == caller || EventHost::can_delete(&event.hosts, &caller),
+            |event| event.is_deleted = true,
+        )
+    }
+
+    // This method is used to add and remove tags on multiple events of a group
+    pub async fn bulk_update_tags(
+        caller: Principal,
+        identifiers: Vec<Principal>,
+        add_tags: Vec<u32>,
+        remove_tags: Vec<u32>,
+        group_identifier: Principal,
+        member_identifier: Principal,
+    ) -> Vec<(Principal, Result<(), ApiError>)> {
+        let has_permission = Self::check_permission(
+            caller,
+            group_identifier,
+            member_identifier,
+            PermissionActionType::Edit,
+        )
+        .await;
+
+        Self::bulk_update_events(
+            identifiers,
+            group_identifier,
+            has_permission,
+            "bulk_update_tags",
+            |event| {
+                event.owner == caller
+                    || EventHost::has_ability(&event.hosts, &caller, &EventHostAbility::EditDetails)
+            },
+            |event| {
+                event.tags.retain(|tag| !remove_tags.contains(tag));
+                for tag in &add_tags {
+                    if !event.tags.contains(tag) {
+                        event.tags.push(*tag);
+                    }
                 }
+            },
+        )
+    }
+
+    // This method is used to propose an ownership transfer for multiple events of a group
+    // the new owner needs to accept the transfer for every event
+    pub async fn bulk_transfer_ownership(
+        caller: Principal,
+        identifiers: Vec<Principal>,
+        new_owner: Principal,
+        group_identifier: Principal,
+        member_identifier: Principal,
+    ) -> Vec<(Principal, Result<(), ApiError>)> {
+        let has_permission = Self::check_permission(
+            caller,
+            group_identifier,
+            member_identifier,
+            PermissionActionType::Edit,
+        )
+        .await;
+
+        Self::bulk_update_events(
+            identifiers,
+            group_identifier,
+            has_permission,
+            "bulk_transfer_ownership",
+            |event| event.owner == caller,
+            |event| Self::set_owner_transfer(event, caller, new_owner),
+        )
+    }
+
+    // This method is used to apply the same change to multiple events of a group
+    // `has_permission` is the group permission that is checked once for all the events,
+    // `is_allowed` decides which events the caller can change without it (owner or host)
+    fn bulk_update_events(
+        identifiers: Vec<Principal>,
+        group_identifier: Principal,
+        has_permission: Result<Principal, ApiError>,
+        method_name: &str,
+        is_allowed: impl Fn(&Event) -> bool,
+        update: impl Fn(&mut Event),
+    ) -> Vec<(Principal, Result<(), ApiError>)> {
+        let name = STABLE_DATA.with(|data| Data::get_name(data.borrow().get()));
+
+        let results: Vec<(Principal, Result<(), ApiError>)> = identifiers
+            .into_iter()
+            .map(|identifier| {
+                let result = STABLE_DATA.with(|data| {
+                    ENTRIES.with(|entries| {
+                        let (_identifier, mut _event) = Data::get_entry(data, entries, identifier)?;
+
+                        if _event.group_identifier != group_identifier
+                            || _event.is_deleted
+                            || _event.is_pending
+                        {
+                            return Err(api_error(
+                                ApiErrorType::NotFound,
+                                "EVENT_NOT_FOUND",
+                                "No event found for this group",
+                                name.as_str(),
+                                method_name,
+                                Some(vec![format!("identifier - {}", &identifier.to_string())]),
+                            ));
+                        }
+
+                        if !is_allowed(&_event) {
+                            has_permission.clone()?;
+                        }
+
+                        update(&mut _event);
+                        _event.updated_on = time();
+                        _event.version += 1;
+
+                        Data::update_entry(data, entries, _identifier, _event).map(|_| ())
+                    })
+                });
+                (identifier, result)
+            })
+            .collect();
+
+        if results.iter().any(|(_, result)| result.is_ok()) {
+            Self::update_data_version();
+        }
+
+        results
+    }
+
+    // EVENT HOSTS
+    // Co-hosts and moderators can edit, cancel or check in attendees of an event based on their abilities
+
+    // This method is used to add a host to an event, an existing host is replaced
+    pub fn add_event_host(
+        caller: Principal,
+        identifier: Principal,
+        host: Principal,
+        role: EventHostRole,
+        abilities: Vec<EventHostAbility>,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "add_event_host")?;
+
+        if _event.owner == host {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "HOST_IS_OWNER",
+                "The owner of the event cant be added as a host",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "add_event_host",
+                None,
+            ));
+        }
+
+        _event.hosts.retain(|_host| _host.principal != host);
+        _event.hosts.push(EventHost {
+            principal: host,
+            role,
+            abilities,
+            added_by: caller,
+            created_on: time(),
+        });
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to remove a host from an event
+    pub fn remove_event_host(
+        identifier: Principal,
+        host: Principal,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "remove_event_host")?;
+
+        if EventHost::find(&_event.hosts, &host).is_none() {
+            return Err(api_error(
+                ApiErrorType::NotFound,
+                "HOST_NOT_FOUND",
+                "The principal is not a host of the event",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "remove_event_host",
+                None,
+            ));
+        }
+
+        _event.hosts.retain(|_host| _host.principal != host);
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to check if a principal is the owner or a host of the event with the ability
+    pub fn has_event_host_ability(
+        identifier: Principal,
+        principal: Principal,
+        ability: EventHostAbility,
+    ) -> bool {
+        match STABLE_DATA
+            .with(|data| ENTRIES.with(|entries| Data::get_entry(data, entries, identifier)))
+        {
+            Ok((_, _event)) => {
+                !_event.is_deleted
+                    && !_event.is_pending
+                    && (_event.owner == principal
+                        || EventHost::has_ability(&_event.hosts, &principal, &ability))
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Store the updated event with a new version and map it to a response
+    fn update_event_entry(
+        identifier: Principal,
+        mut event: Event,
+    ) -> Result<EventResponse, ApiError> {
+        event.version += 1;
+        let (_identifier, _event) = STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| Data::update_entry(data, entries, identifier, event))
+        })?;
+        Self::update_data_version();
+
+        Ok(Self::map_to_event_response(_identifier.to_string(), _event))
+    }
+
+    // EVENT ATTENDEE CANISTER
+    // The event attendee canister is configured on the parent canister, it is never taken from the caller
+
+    // This method is used to store the event attendee canister pushed by the parent canister
+    pub fn store_event_attendee_canister(event_attendee_canister: Principal) {
+        let _ = EVENT_ATTENDEE_CANISTER.with(|canister| {
+            canister
+                .borrow_mut()
+                .set(event_attendee_canister.as_slice().to_vec())
+        });
+    }
+
+    // Get the event attendee canister, it is fetched from the parent canister when it isnt pushed yet (inter-canister call)
+    pub async fn get_event_attendee_canister(method_name: &str) -> Result<Principal, ApiError> {
+        let stored = EVENT_ATTENDEE_CANISTER.with(|canister| canister.borrow().get().clone());
+        if !stored.is_empty() {
+            return Ok(Principal::from_slice(&stored));
+        }
+
+        let parent = STABLE_DATA.with(|data| data.borrow().get().parent);
+        let result: Result<(Option<Principal>,), _> =
+            call::call(parent, "get_event_attendee_canister", ()).await;
+
+        match result {
+            Ok((Some(_event_attendee_canister),)) => {
+                Self::store_event_attendee_canister(_event_attendee_canister);
+                Ok(_event_attendee_canister)
+            }
+            Ok((None,)) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "EVENT_ATTENDEE_CANISTER_NOT_SET",
+                "The event attendee canister isnt configured on the parent canister",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                method_name,
+                None,
+            )),
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_FETCH_EVENT_ATTENDEE_CANISTER",
+                err.1.as_str(),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                method_name,
+                None,
+            )),
+        }
+    }
+
+    // TICKETS
+    // Tickets are sold per tier, the sold tickets are counted on the event and the holders are recorded on the event attendee canister
+
+    // This method is used to add a ticket tier to an event
+    pub fn add_ticket_tier(
+        identifier: Principal,
+        tier: PostTicketTier,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        validate_ticket_tier(&tier)?;
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "add_ticket_tier")?;
+
+        if _event.ticket_tiers.len() >= MAX_TICKET_TIERS {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "MAX_TICKET_TIERS",
+                &format!(
+                    "An event can't have more than {} ticket tiers",
+                    MAX_TICKET_TIERS
+                ),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "add_ticket_tier",
+                None,
+            ));
+        }
+
+        let id = _event
+            .ticket_tiers
+            .iter()
+            .map(|_tier| _tier.id + 1)
+            .max()
+            .unwrap_or(0);
+
+        _event.ticket_tiers.push(TicketTier {
+            id,
+            name: tier.name,
+            price: tier.price,
+            supply: tier.supply,
+            sold: 0,
+            sale_start: tier.sale_start,
+            sale_end: tier.sale_end,
+            created_on: time(),
+        });
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to update a ticket tier, the price can only be changed before tickets are sold
+    // and the supply can't be lowered below the amount of sold tickets
+    pub fn update_ticket_tier(
+        identifier: Principal,
+        tier_id: u32,
+        tier: PostTicketTier,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        validate_ticket_tier(&tier)?;
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "update_ticket_tier")?;
+
+        let _tier = Self::get_ticket_tier_mut(&mut _event, tier_id, "update_ticket_tier")?;
+        if _tier.sold > 0 && _tier.price != tier.price {
+            return Err(Self::ticket_error(
+                "TICKETS_SOLD",
+                "The price can't be changed after tickets are sold",
+                "update_ticket_tier",
+            ));
+        }
+        if tier.supply < _tier.sold {
+            return Err(Self::ticket_error(
+                "SUPPLY_BELOW_SOLD",
+                "The supply can't be lower than the amount of sold tickets",
+                "update_ticket_tier",
+            ));
+        }
+
+        _tier.name = tier.name;
+        _tier.price = tier.price;
+        _tier.supply = tier.supply;
+        _tier.sale_start = tier.sale_start;
+        _tier.sale_end = tier.sale_end;
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to remove a ticket tier, only tiers without sold tickets can be removed
+    pub fn remove_ticket_tier(
+        identifier: Principal,
+        tier_id: u32,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "remove_ticket_tier")?;
+
+        if Self::get_ticket_tier_mut(&mut _event, tier_id, "remove_ticket_tier")?.sold > 0 {
+            return Err(Self::ticket_error(
+                "TICKETS_SOLD",
+                "A ticket tier with sold tickets can't be removed",
+                "remove_ticket_tier",
+            ));
+        }
+
+        _event.ticket_tiers.retain(|_tier| _tier.id != tier_id);
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to claim a ticket of a free tier, the caller is recorded as holder on the event attendee canister
+    pub async fn claim_ticket(
+        caller: Principal,
+        identifier: Principal,
+        tier_id: u32,
+        group_identifier: Principal,
+    ) -> Result<Ticket, ApiError> {
+        let (_, _event) = Self::get_group_entry(identifier, group_identifier, "claim_ticket")?;
+        if !Self::get_ticket_tier(&_event, tier_id, "claim_ticket")?
+            .price
+            .is_free()
+        {
+            return Err(Self::ticket_error(
+                "PAYMENT_REQUIRED",
+                "Tickets of this tier need to be paid",
+                "claim_ticket",
+            ));
+        }
+
+        Self::issue_ticket(caller, identifier, tier_id, group_identifier, None).await
+    }
+
+    // This method is used to buy a ticket, the payment is collected on the ledger of the tier before the holder is recorded
+    pub async fn buy_ticket(
+        caller: Principal,
+        identifier: Principal,
+        tier_id: u32,
+        payment_method: TicketPaymentMethod,
+        group_identifier: Principal,
+    ) -> Result<Ticket, ApiError> {
+        let (_, _event) = Self::get_group_entry(identifier, group_identifier, "buy_ticket")?;
+        let is_free = Self::get_ticket_tier(&_event, tier_id, "buy_ticket")?
+            .price
+            .is_free();
+
+        Self::issue_ticket(
+            caller,
+            identifier,
+            tier_id,
+            group_identifier,
+            match is_free {
+                true => None,
+                false => Some(payment_method),
+            },
+        )
+        .await
+    }
+
+    // Reserve a ticket, collect the payment and record the holder on the event attendee canister (inter-canister calls)
+    // every step is undone when a later step fails, the reserved ticket is released and the payment is refunded
+    async fn issue_ticket(
+        holder: Principal,
+        identifier: Principal,
+        tier_id: u32,
+        group_identifier: Principal,
+        payment_method: Option<TicketPaymentMethod>,
+    ) -> Result<Ticket, ApiError> {
+        let event_attendee_canister = Self::get_event_attendee_canister("issue_ticket").await?;
+
+        // What is left on the payment subaccount is returned after an ICRC-1 payment, so only one ICRC-1 payment per buyer is in progress
+        let _account_guard = match payment_method {
+            Some(TicketPaymentMethod::Icrc1Transfer) => {
+                match TicketPaymentGuard::new(format!("{}-{}", identifier, holder)) {
+                    Some(_guard) => Some(_guard),
+                    None => {
+                        return Err(Self::ticket_error(
+                            "PAYMENT_IN_PROGRESS",
+                            "Another ICRC-1 payment of the caller is in progress",
+                            "issue_ticket",
+                        ))
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let price = Self::reserve_ticket(identifier, tier_id, group_identifier)?;
+
+        // The payment is stored before the ledger is called, so an interrupted payment can be resolved with `refund_ticket_payments`
+        // the guard keeps the payment from being resolved while the calls of this purchase are awaited
+        let payment = match payment_method {
+            None => None,
+            Some(_payment_method) => {
+                let (_payment_key, _guard) = Self::start_ticket_payment(
+                    holder,
+                    identifier,
+                    tier_id,
+                    &price,
+                    _payment_method,
+                );
+                match Self::collect_payment(&_payment_key).await {
+                    Ok(_) => {
+                        if _account_guard.is_some() {
+                            Self::return_ticket_overpayment(&_payment_key).await;
+                        }
+                        Some((_payment_key, _guard))
+                    }
+                    Err(err) => {
+                        Self::release_ticket(identifier, tier_id);
+                        return Err(err);
+                    }
+                }
+            }
+        };
+
+        let add_holder_response: Result<(Result<(), bool>,), _> = call::call(
+            event_attendee_canister,
+            "add_ticket_holder_as_attendee",
+            (holder, identifier, group_identifier, tier_id),
+        )
+        .await;
+
+        match add_holder_response {
+            Ok((Ok(_),)) => Ok(Ticket {
+                event_identifier: identifier,
+                tier_id,
+                holder,
+                price,
+                created_on: time(),
+            }),
+            _ => {
+                Self::release_ticket(identifier, tier_id);
+                if let Some((_payment_key, _guard)) = payment {
+                    let _ = Self::refund_ticket_payment(_payment_key).await;
+                }
+                Err(Self::ticket_error(
+                    "HOLDER_ADD_FAILED",
+                    "Storing the ticket holder failed",
+                    "issue_ticket",
+                ))
+            }
+        }
+    }
+
+    // Count a ticket as sold when the tier is on sale, the sold count is part of the event so the version is increased
+    fn reserve_ticket(
+        identifier: Principal,
+        tier_id: u32,
+        group_identifier: Principal,
+    ) -> Result<TicketPrice, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "reserve_ticket")?;
+
+        if _event.is_canceled.0 {
+            return Err(Self::ticket_error(
+                "EVENT_CANCELED",
+                "Tickets can't be sold for a canceled event",
+                "reserve_ticket",
+            ));
+        }
+
+        let now = time();
+        let _tier = Self::get_ticket_tier_mut(&mut _event, tier_id, "reserve_ticket")?;
+        if !_tier.is_on_sale(now) {
+            return Err(Self::ticket_error(
+                "TIER_NOT_ON_SALE",
+                "The ticket tier is sold out or not on sale",
+                "reserve_ticket",
+            ));
+        }
+        _tier.sold += 1;
+        let price = _tier.price.clone();
+        _event.updated_on = now;
+
+        Self::update_event_entry(_identifier, _event)?;
+        Ok(price)
+    }
+
+    // Undo a reserved ticket, the event is read again because it could be changed during the inter-canister call
+    fn release_ticket(identifier: Principal, tier_id: u32) {
+        if let Ok((_identifier, mut _event)) = STABLE_DATA
+            .with(|data| ENTRIES.with(|entries| Data::get_entry(data, entries, identifier)))
+        {
+            if let Some(_tier) = _event
+                .ticket_tiers
+                .iter_mut()
+                .find(|_tier| _tier.id == tier_id)
+            {
+                _tier.sold = _tier.sold.saturating_sub(1);
+            }
+            _event.updated_on = time();
+
+            let _ = Self::update_event_entry(_identifier, _event);
+        }
+    }
+
+    // TICKET PAYMENTS
+    // Payments are collected on the subaccount of the event and refunded from it when the event is canceled
+
+    // This method is used to get the account a buyer transfers the ticket price (plus fee) to when paying with an ICRC-1 transfer
+    pub fn get_ticket_payment_account(identifier: Principal, payer: Principal) -> Account {
+        Account {
+            owner: id(),
+            subaccount: Some(ticket_payment_subaccount(&identifier, &payer)),
+        }
+    }
+
+    // This method is used to get the collected payments of an event
+    pub fn get_ticket_payments(identifier: Principal) -> Vec<TicketPayment> {
+        Self::get_ticket_payment_keys(identifier)
+            .into_iter()
+            .filter_map(|key| TICKET_PAYMENTS.with(|payments| payments.borrow().get(&key)))
+            .collect()
+    }
+
+    // This method is used to resolve the interrupted payments of an event and to refund the payments of a canceled event
+    // interrupted payments are resubmitted to the ledger, which returns the original transfer when it went through
+    pub async fn refund_ticket_payments(
+        identifier: Principal,
+        group_identifier: Principal,
+    ) -> Result<Vec<TicketPayment>, ApiError> {
+        let (_, _event) =
+            Self::get_group_entry(identifier, group_identifier, "refund_ticket_payments")?;
+
+        let mut refunded = vec![];
+        for key in Self::get_ticket_payment_keys(identifier) {
+            // Payments of which the calls are still awaited are skipped
+            let _guard = match TicketPaymentGuard::new(key.clone()) {
+                Some(_guard) => _guard,
+                None => continue,
+            };
+
+            let payment = match TICKET_PAYMENTS.with(|payments| payments.borrow().get(&key)) {
+                Some(_payment) => _payment,
+                None => continue,
+            };
+
+            let result = match payment.status {
+                // The purchase was interrupted before the holder was recorded, the collected payment is refunded
+                TicketPaymentStatus::Collecting { .. } => {
+                    let is_collected = Self::collect_payment(&key).await.is_ok();
+                    Self::release_ticket(identifier, payment.tier_id);
+                    match is_collected {
+                        true => Self::refund_ticket_payment(key).await,
+                        false => None,
+                    }
+                }
+                TicketPaymentStatus::Refunding { .. } => Self::refund_ticket_payment(key).await,
+                TicketPaymentStatus::Paid | TicketPaymentStatus::RefundFailed(_)
+                    if _event.is_canceled.0 =>
+                {
+                    Self::refund_ticket_payment(key).await
+                }
+                _ => None,
+            };
+
+            if let Some(_payment) = result {
+                refunded.push(_payment);
+            }
+        }
+        Ok(refunded)
+    }
+
+    // Store the payment of a ticket before its price is collected, the key is unique for the payment
+    // returns the key with the guard that marks the payment as in progress
+    fn start_ticket_payment(
+        payer: Principal,
+        identifier: Principal,
+        tier_id: u32,
+        price: &TicketPrice,
+        payment_method: TicketPaymentMethod,
+    ) -> (String, TicketPaymentGuard) {
+        let created_at_time = time();
+        let refund_account = match &payment_method {
+            TicketPaymentMethod::Icrc2Approval { from_subaccount } => Account {
+                owner: payer,
+                subaccount: from_subaccount.clone(),
+            },
+            TicketPaymentMethod::Icrc1Transfer => Account {
+                owner: payer,
+                subaccount: None,
+            },
+        };
+
+        let payment = TicketPayment {
+            event_identifier: identifier,
+            tier_id,
+            payer,
+            refund_account,
+            payment_method,
+            ledger: price.ledger,
+            amount: price.amount.clone(),
+            block_index: None,
+            status: TicketPaymentStatus::Collecting { created_at_time },
+            created_on: created_at_time,
+        };
+
+        TICKET_PAYMENTS.with(|payments| {
+            let mut payments = payments.borrow_mut();
+            let mut nonce: u64 = 0;
+            loop {
+                let key = format!("{}-{}-{}-{}", identifier, payer, created_at_time, nonce);
+                if !payments.contains_key(&key) {
+                    payments.insert(key.clone(), payment);
+                    let guard = TicketPaymentGuard::new(key.clone())
+                        .expect("a new payment key can't be in progress");
+                    return (key, guard);
+                }
+                nonce += 1;
+            }
+        })
+    }
+
+    // Collect the price of a collecting payment on the ledger of the tier (inter-canister call)
+    // the payment is marked as paid when it is collected and removed when the ledger refused it
+    async fn collect_payment(key: &str) -> Result<(), ApiError> {
+        let mut payment =
+            match TICKET_PAYMENTS.with(|payments| payments.borrow().get(&key.to_string())) {
+                Some(_payment) => _payment,
+                None => {
+                    return Err(Self::ticket_error(
+                        "PAYMENT_NOT_FOUND",
+                        "The ticket payment is not found",
+                        "collect_payment",
+                    ))
+                }
+            };
+
+        let created_at_time = match payment.status {
+            TicketPaymentStatus::Collecting { created_at_time } => created_at_time,
+            _ => return Ok(()),
+        };
+
+        let result = Ledger::new(payment.ledger, id())
+            .collect(
+                &payment.event_identifier,
+                payment.payer,
+                payment.amount.clone(),
+                &payment.payment_method,
+                created_at_time,
+                ticket_payment_memo(key),
+            )
+            .await;
+
+        match result {
+            Ok(block_index) => {
+                payment.block_index = Some(block_index);
+                payment.status = TicketPaymentStatus::Paid;
+                TICKET_PAYMENTS
+                    .with(|payments| payments.borrow_mut().insert(key.to_string(), payment));
+                Ok(())
+            }
+            Err(err) => {
+                TICKET_PAYMENTS.with(|payments| payments.borrow_mut().remove(&key.to_string()));
+                Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "PAYMENT_FAILED",
+                    err.as_str(),
+                    STABLE_DATA
+                        .with(|data| Data::get_name(data.borrow().get()))
+                        .as_str(),
+                    "collect_payment",
+                    Some(vec![format!("ledger - {}", &payment.ledger.to_string())]),
+                ))
+            }
+        }
+    }
+
+    // Return what the buyer transferred more than the price (plus fee) of an ICRC-1 payment (inter-canister calls)
+    // a failed return stays on the payment subaccount and is returned after the next ICRC-1 payment of the buyer
+    async fn return_ticket_overpayment(key: &str) {
+        if let Some(payment) =
+            TICKET_PAYMENTS.with(|payments| payments.borrow().get(&key.to_string()))
+        {
+            let _ = Ledger::new(payment.ledger, id())
+                .return_overpayment(
+                    &payment.event_identifier,
+                    payment.payer,
+                    payment.refund_account,
+                    time(),
+                    ticket_payment_memo(key),
+                )
+                .await;
+        }
+    }
+
+    // Refund a payment minus the ledger fee to the account it was paid from (inter-canister calls)
+    // the refund is stored as refunding before the ledger is called, an interrupted refund is resubmitted with the same created_at_time
+    async fn refund_ticket_payment(key: String) -> Option<TicketPayment> {
+        let mut payment = TICKET_PAYMENTS.with(|payments| payments.borrow().get(&key))?;
+        let created_at_time = match payment.status {
+            TicketPaymentStatus::Refunding { created_at_time } => created_at_time,
+            TicketPaymentStatus::Paid | TicketPaymentStatus::RefundFailed(_) => time(),
+            _ => return None,
+        };
+
+        payment.status = TicketPaymentStatus::Refunding { created_at_time };
+        TICKET_PAYMENTS.with(|payments| payments.borrow_mut().insert(key.clone(), payment.clone()));
+
+        let result = Ledger::new(payment.ledger, id())
+            .refund(
+                &payment.event_identifier,
+                payment.refund_account.clone(),
+                payment.amount.clone(),
+                created_at_time,
+                ticket_payment_memo(&key),
+            )
+            .await;
+
+        payment.status = match result {
+            Ok(block_index) => TicketPaymentStatus::Refunded {
+                block_index,
+                refunded_on: time(),
+            },
+            Err(err) => TicketPaymentStatus::RefundFailed(err),
+        };
+        TICKET_PAYMENTS.with(|payments| payments.borrow_mut().insert(key, payment.clone()));
+        Some(payment)
+    }
+
+    // The payments of an event are stored with the event identifier as prefix of the key
+    fn get_ticket_payment_keys(identifier: Principal) -> Vec<String> {
+        let prefix = format!("{}-", identifier);
+        TICKET_PAYMENTS.with(|payments| {
+            payments
+                .borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .map(|(key, _)| key)
+                .collect()
+        })
+    }
+
+    fn get_ticket_tier<'a>(
+        event: &'a Event,
+        tier_id: u32,
+        method_name: &str,
+    ) -> Result<&'a TicketTier, ApiError> {
+        TicketTier::find(&event.ticket_tiers, tier_id)
+            .ok_or_else(|| Self::ticket_tier_not_found(tier_id, method_name))
+    }
+
+    fn get_ticket_tier_mut<'a>(
+        event: &'a mut Event,
+        tier_id: u32,
+        method_name: &str,
+    ) -> Result<&'a mut TicketTier, ApiError> {
+        event
+            .ticket_tiers
+            .iter_mut()
+            .find(|_tier| _tier.id == tier_id)
+            .ok_or_else(|| Self::ticket_tier_not_found(tier_id, method_name))
+    }
+
+    fn ticket_tier_not_found(tier_id: u32, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::NotFound,
+            "TICKET_TIER_NOT_FOUND",
+            "The ticket tier is not found",
+            STABLE_DATA
+                .with(|data| Data::get_name(data.borrow().get()))
+                .as_str(),
+            method_name,
+            Some(vec![format!("tier_id - {}", tier_id)]),
+        )
+    }
+
+    fn ticket_error(tag: &str, message: &str, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::BadRequest,
+            tag,
+            message,
+            STABLE_DATA
+                .with(|data| Data::get_name(data.borrow().get()))
+                .as_str(),
+            method_name,
+            None,
+        )
+    }
+
+    // SESSIONS
+    // The agenda of an event, sessions are stored on the event and ordered by their start date
+
+    // This method is used to add a session to the agenda of an event
+    pub fn add_event_session(
+        identifier: Principal,
+        session: PostEventSession,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "add_event_session")?;
+        validate_event_session(&session, &_event.date)?;
+
+        if _event.sessions.len() >= MAX_EVENT_SESSIONS {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "MAX_EVENT_SESSIONS",
+                &format!(
+                    "An event can't have more than {} sessions",
+                    MAX_EVENT_SESSIONS
+                ),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "add_event_session",
+                None,
+            ));
+        }
+
+        let id = _event
+            .sessions
+            .iter()
+            .map(|_session| _session.id + 1)
+            .max()
+            .unwrap_or(0);
+
+        _event.sessions.push(EventSession {
+            id,
+            title: session.title,
+            description: session.description,
+            speakers: session.speakers,
+            date: session.date,
+            location: session.location,
+            tags: session.tags,
+            updated_on: time(),
+            created_on: time(),
+        });
+        _event
+            .sessions
+            .sort_by_key(|_session| (_session.date.start_date, _session.id));
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to update a session of an event
+    pub fn update_event_session(
+        identifier: Principal,
+        session_id: u32,
+        session: PostEventSession,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "update_event_session")?;
+        validate_event_session(&session, &_event.date)?;
+
+        let _session = _event
+            .sessions
+            .iter_mut()
+            .find(|_session| _session.id == session_id)
+            .ok_or_else(|| Self::session_not_found(session_id, "update_event_session"))?;
+
+        _session.title = session.title;
+        _session.description = session.description;
+        _session.speakers = session.speakers;
+        _session.date = session.date;
+        _session.location = session.location;
+        _session.tags = session.tags;
+        _session.updated_on = time();
+
+        _event
+            .sessions
+            .sort_by_key(|_session| (_session.date.start_date, _session.id));
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to remove a session from an event
+    pub fn remove_event_session(
+        identifier: Principal,
+        session_id: u32,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "remove_event_session")?;
+
+        if !_event
+            .sessions
+            .iter()
+            .any(|_session| _session.id == session_id)
+        {
+            return Err(Self::session_not_found(session_id, "remove_event_session"));
+        }
+
+        _event.sessions.retain(|_session| _session.id != session_id);
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to export a session as an iCalendar (.ics) file
+    pub fn get_event_session_ics(
+        identifier: Principal,
+        session_id: u32,
+        group_identifier: Principal,
+    ) -> Result<String, ApiError> {
+        let (_identifier, _event) =
+            Self::get_group_entry(identifier, group_identifier, "get_event_session_ics")?;
+
+        match _event
+            .sessions
+            .iter()
+            .find(|_session| _session.id == session_id)
+        {
+            Some(_session) => Ok(_session.to_ics(&_identifier, &_event.name, time())),
+            None => Err(Self::session_not_found(session_id, "get_event_session_ics")),
+        }
+    }
+
+    fn session_not_found(session_id: u32, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::NotFound,
+            "SESSION_NOT_FOUND",
+            "The session is not found",
+            STABLE_DATA
+                .with(|data| Data::get_name(data.borrow().get()))
+                .as_str(),
+            method_name,
+            Some(vec![format!("session_id - {}", session_id)]),
+        )
+    }
+
+    // SPEAKERS AND SPONSORS
+
+    // This method is used to add a speaker or sponsor to an event
+    pub fn add_event_profile(
+        identifier: Principal,
+        kind: EventProfileKind,
+        profile: PostEventProfile,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        validate_event_profile(&profile)?;
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "add_event_profile")?;
+
+        let profiles = Self::get_event_profiles_mut(&mut _event, &kind);
+        if profiles.len() >= MAX_EVENT_PROFILES {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "MAX_EVENT_PROFILES",
+                &format!(
+                    "An event can't have more than {} {:?} profiles",
+                    MAX_EVENT_PROFILES, kind
+                ),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "add_event_profile",
+                None,
+            ));
+        }
+
+        let id = profiles
+            .iter()
+            .map(|_profile| _profile.id + 1)
+            .max()
+            .unwrap_or(0);
+
+        profiles.push(EventProfile {
+            id,
+            name: profile.name,
+            principal: profile.principal,
+            bio: profile.bio,
+            image: profile.image,
+            link: profile.link,
+            updated_on: time(),
+            created_on: time(),
+        });
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to update a speaker or sponsor of an event
+    pub fn update_event_profile(
+        identifier: Principal,
+        kind: EventProfileKind,
+        profile_id: u32,
+        profile: PostEventProfile,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        validate_event_profile(&profile)?;
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "update_event_profile")?;
+
+        let _profile = Self::get_event_profiles_mut(&mut _event, &kind)
+            .iter_mut()
+            .find(|_profile| _profile.id == profile_id)
+            .ok_or_else(|| Self::profile_not_found(profile_id, "update_event_profile"))?;
+
+        _profile.name = profile.name;
+        _profile.principal = profile.principal;
+        _profile.bio = profile.bio;
+        _profile.image = profile.image;
+        _profile.link = profile.link;
+        _profile.updated_on = time();
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    // This method is used to remove a speaker or sponsor from an event
+    pub fn remove_event_profile(
+        identifier: Principal,
+        kind: EventProfileKind,
+        profile_id: u32,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "remove_event_profile")?;
+
+        let profiles = Self::get_event_profiles_mut(&mut _event, &kind);
+        if !profiles.iter().any(|_profile| _profile.id == profile_id) {
+            return Err(Self::profile_not_found(profile_id, "remove_event_profile"));
+        }
+
+        profiles.retain(|_profile| _profile.id != profile_id);
+        _event.updated_on = time();
+
+        Self::update_event_entry(_identifier, _event)
+    }
+
+    fn get_event_profiles_mut<'a>(
+        event: &'a mut Event,
+        kind: &EventProfileKind,
+    ) -> &'a mut Vec<EventProfile> {
+        match kind {
+            EventProfileKind::Speaker => &mut event.speakers,
+            EventProfileKind::Sponsor => &mut event.sponsors,
+        }
+    }
+
+    fn profile_not_found(profile_id: u32, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::NotFound,
+            "PROFILE_NOT_FOUND",
+            "The speaker or sponsor is not found",
+            STABLE_DATA
+                .with(|data| Data::get_name(data.borrow().get()))
+                .as_str(),
+            method_name,
+            Some(vec![format!("profile_id - {}", profile_id)]),
+        )
+    }
+
+    // CHECK-IN
+    // Attendees of physical events get a signed check-in code that hosts scan at the door
+
+    // This method is used to get the check-in code of the caller for a physical event, only attendees and ticket holders get a code
+    pub async fn get_check_in_code(
+        caller: Principal,
+        identifier: Principal,
+        group_identifier: Principal,
+    ) -> Result<String, ApiError> {
+        let (_identifier, _event) =
+            Self::get_group_entry(identifier, group_identifier, "get_check_in_code")?;
+        Self::check_physical_event(&_event, "get_check_in_code")?;
+        Self::check_event_attendee(caller, _identifier, "get_check_in_code").await?;
+
+        let secret = Self::get_check_in_secret("get_check_in_code").await?;
+        // The code can be used until the end of the event
+        let expires_at = match _event.date.end_date {
+            0 => _event.date.start_date + CHECK_IN_CODE_TTL,
+            _ => _event.date.end_date,
+        };
+
+        Ok(CheckInCode {
+            event_identifier: _identifier,
+            attendee: caller,
+            expires_at,
+        }
+        .sign(&secret))
+    }
+
+    // This method is used to check in an attendee with their check-in code, an attendee can only check in once
+    pub async fn check_in(
+        caller: Principal,
+        identifier: Principal,
+        code: String,
+        group_identifier: Principal,
+    ) -> Result<CheckIn, ApiError> {
+        let (_identifier, _event) =
+            Self::get_group_entry(identifier, group_identifier, "check_in")?;
+        Self::check_physical_event(&_event, "check_in")?;
+
+        let secret = Self::get_check_in_secret("check_in").await?;
+        let check_in_code = match CheckInCode::verify(&code, &_identifier, &secret, time()) {
+            Ok(_check_in_code) => _check_in_code,
+            Err(CheckInCodeError::Invalid) => {
+                return Err(Self::check_in_error(
+                    "INVALID_CHECK_IN_CODE",
+                    "The check-in code is not valid",
+                    "check_in",
+                ))
+            }
+            Err(CheckInCodeError::WrongEvent) => {
+                return Err(Self::check_in_error(
+                    "WRONG_EVENT_CHECK_IN_CODE",
+                    "The check-in code is for another event",
+                    "check_in",
+                ))
+            }
+            Err(CheckInCodeError::Expired) => {
+                return Err(Self::check_in_error(
+                    "CHECK_IN_CODE_EXPIRED",
+                    "The check-in code is expired",
+                    "check_in",
+                ))
+            }
+        };
+
+        let key = format!("{}-{}", _identifier, check_in_code.attendee);
+        if let Some(_check_in) = CHECK_INS.with(|check_ins| check_ins.borrow().get(&key)) {
+            return Err(Self::check_in_error(
+                "ALREADY_CHECKED_IN",
+                &format!(
+                    "The attendee is already checked in at {}",
+                    _check_in.checked_in_on
+                ),
+                "check_in",
+            ));
+        }
+
+        let check_in = CheckIn {
+            event_identifier: _identifier,
+            attendee: check_in_code.attendee,
+            checked_in_by: caller,
+            checked_in_on: time(),
+        };
+        CHECK_INS.with(|check_ins| check_ins.borrow_mut().insert(key, check_in.clone()));
+        Ok(check_in)
+    }
+
+    // This method is used to get the amount of registered attendees and the amount of attendees that checked in
+    pub fn get_check_in_stats(
+        identifier: Principal,
+        group_identifier: Principal,
+    ) -> Result<CheckInStats, ApiError> {
+        let (_identifier, _event) =
+            Self::get_group_entry(identifier, group_identifier, "get_check_in_stats")?;
+
+        let prefix = format!("{}-", _identifier);
+        let checked_in = CHECK_INS.with(|check_ins| {
+            check_ins
+                .borrow()
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&prefix))
+                .count()
+        });
+
+        Ok(CheckInStats {
+            registered: _event.attendee_count.values().sum(),
+            checked_in,
+        })
+    }
+
+    // Get the secret the check-in codes are signed with, it is fetched from the parent canister when it isnt fetched yet (inter-canister call)
+    // all the child canisters share the secret, so the codes of an event stay valid when the event is moved to another child canister
+    async fn get_check_in_secret(method_name: &str) -> Result<Vec<u8>, ApiError> {
+        let secret = CHECK_IN_SECRET.with(|secret| secret.borrow().get().clone());
+        if !secret.is_empty() {
+            return Ok(secret);
+        }
+
+        let parent = STABLE_DATA.with(|data| data.borrow().get().parent);
+        let result: Result<(Result<Vec<u8>, ApiError>,), _> =
+            call::call(parent, "get_check_in_secret", ()).await;
+
+        match result {
+            Ok((Ok(_secret),)) => {
+                let _ = CHECK_IN_SECRET.with(|secret| secret.borrow_mut().set(_secret.clone()));
+                Ok(_secret)
+            }
+            Ok((Err(err),)) => Err(err),
+            Err(err) => Err(Self::check_in_error(
+                "FAILED_TO_GET_CHECK_IN_SECRET",
+                err.1.as_str(),
+                method_name,
+            )),
+        }
+    }
+
+    // Check if the attendee is registered for the event on the event attendee canister (inter-canister call)
+    // ticket holders are recorded as attendees when their ticket is issued
+    async fn check_event_attendee(
+        attendee: Principal,
+        event_identifier: Principal,
+        method_name: &str,
+    ) -> Result<(), ApiError> {
+        let event_attendee_canister = Self::get_event_attendee_canister(method_name).await?;
+        let result: Result<(bool,), _> = call::call(
+            event_attendee_canister,
+            "is_event_attendee",
+            (attendee, event_identifier),
+        )
+        .await;
+
+        match result {
+            Ok((true,)) => Ok(()),
+            Ok((false,)) => Err(api_error(
+                ApiErrorType::Unauthorized,
+                "NOT_AN_ATTENDEE",
+                "Only attendees and ticket holders of the event get a check-in code",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                method_name,
+                None,
+            )),
+            Err(err) => Err(Self::check_in_error(
+                "FAILED_TO_CHECK_ATTENDEE",
+                err.1.as_str(),
+                method_name,
+            )),
+        }
+    }
+
+    // Check-in is only available for events with a physical location that are not canceled
+    fn check_physical_event(event: &Event, method_name: &str) -> Result<(), ApiError> {
+        if event.is_canceled.0 {
+            return Err(Self::check_in_error(
+                "EVENT_CANCELED",
+                "Check-in is not available for a canceled event",
+                method_name,
+            ));
+        }
+
+        match event.location {
+            Location::Physical(_) | Location::MultiLocation(_) => Ok(()),
+            _ => Err(Self::check_in_error(
+                "NOT_A_PHYSICAL_EVENT",
+                "Check-in is only available for physical events",
+                method_name,
+            )),
+        }
+    }
+
+    fn check_in_error(tag: &str, message: &str, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::BadRequest,
+            tag,
+            message,
+            STABLE_DATA
+                .with(|data| Data::get_name(data.borrow().get()))
+                .as_str(),
+            method_name,
+            None,
+        )
+    }
+
+    // OWNERSHIP TRANSFER
+    // The ownership of an event is transferred in two steps, an editor proposes the new owner and the new owner accepts it
+
+    // This method is used to propose a new owner for an event, a pending proposal is replaced
+    pub fn propose_owner_transfer(
+        caller: Principal,
+        identifier: Principal,
+        new_owner: Principal,
+        group_identifier: Principal,
+    ) -> Result<OwnerTransfer, ApiError> {
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "propose_owner_transfer")?;
+
+        if _event.owner == new_owner {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "ALREADY_OWNER",
+                "The principal is already the owner of the event",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "propose_owner_transfer",
+                None,
+            ));
+        }
+
+        Self::set_owner_transfer(&mut _event, caller, new_owner);
+        _event.version += 1;
+        let owner_transfer = _event.owner_transfer.clone();
+
+        STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
+        })?;
+        Self::update_data_version();
+
+        Ok(owner_transfer.unwrap())
+    }
+
+    // This method is used by the proposed owner to accept the ownership transfer of an event
+    // the new owner is added as an attendee on the event attendee canister
+    pub async fn accept_owner_transfer(
+        caller: Principal,
+        identifier: Principal,
+        group_identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let name = STABLE_DATA.with(|data| Data::get_name(data.borrow().get()));
+        let event_attendee_canister =
+            Self::get_event_attendee_canister("accept_owner_transfer").await?;
+        let (_identifier, mut _event) =
+            Self::get_group_entry(identifier, group_identifier, "accept_owner_transfer")?;
+
+        let owner_transfer = match _event.owner_transfer.take() {
+            Some(_owner_transfer) if _owner_transfer.to == caller => _owner_transfer,
+            _ => {
+                return Err(api_error(
+                    ApiErrorType::NotFound,
+                    "NO_OWNER_TRANSFER",
+                    "No ownership transfer found for this principal",
+                    name.as_str(),
+                    "accept_owner_transfer",
+                    None,
+                ))
             }
+        };
+
+        if owner_transfer.expires_at < time() {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "OWNER_TRANSFER_EXPIRED",
+                "The ownership transfer is expired",
+                name.as_str(),
+                "accept_owner_transfer",
+                None,
+            ));
+        }
+
+        _event.owner = owner_transfer.to;
+        _event.owner_transfer_history.push(OwnerTransferRecord {
+            action: OwnerTransferAction::Accepted,
+            from: owner_transfer.from,
+            to: owner_transfer.to,
+            by: caller,
+            created_on: time(),
+        });
+        _event.updated_on = time();
+        _event.version += 1;
+
+        let (_identifier, _event) = STABLE_DATA.with(|data| {
+            ENTRIES.with(|entries| Data::update_entry(data, entries, _identifier, _event))
+        })?;
+        Self::update_data_version();
+
+        let _ = Self::add_owner_as_attendee(
+            &_event.owner,
+            &_identifier,
+            &group_identifier,
+            &event_attendee_canister,
+        )
+        .await;
+
+        Ok(Self::map_to_event_response(_identifier.to_string(), _event))
+    }
+
+    // This method is used to get the pending ownership transfer and the ownership transfer history of an event
+    pub fn get_owner_transfers(
+        identifier: Principal,
+        group_identifier: Principal,
+    ) -> Result<(Option<OwnerTransfer>, Vec<OwnerTransferRecord>), ApiError> {
+        let (_, _event) =
+            Self::get_group_entry(identifier, group_identifier, "get_owner_transfers")?;
+        Ok((_event.owner_transfer, _event.owner_transfer_history))
+    }
+
+    // Set the pending ownership transfer on the event and add it to the history
+    fn set_owner_transfer(event: &mut Event, caller: Principal, new_owner: Principal) {
+        let now = time();
+        event.owner_transfer = Some(OwnerTransfer {
+            from: event.owner,
+            to: new_owner,
+            proposed_by: caller,
+            expires_at: now + OWNER_TRANSFER_TTL,
+            created_on: now,
+        });
+        event.owner_transfer_history.push(OwnerTransferRecord {
+            action: OwnerTransferAction::Proposed,
+            from: event.owner,
+            to: new_owner,
+            by: caller,
+            created_on: now,
+        });
+    }
+
+    // Get a (not deleted) event that belongs to the group
+    fn get_group_entry(
+        identifier: Principal,
+        group_identifier: Principal,
+        method_name: &str,
+    ) -> Result<(Principal, Event), ApiError> {
+        let (_identifier, _event) = STABLE_DATA
+            .with(|data| ENTRIES.with(|entries| Data::get_entry(data, entries, identifier)))?;
+
+        if _event.group_identifier != group_identifier || _event.is_deleted || _event.is_pending {
+            return Err(api_error(
+                ApiErrorType::NotFound,
+                "EVENT_NOT_FOUND",
+                "No event found for this group",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                method_name,
+                None,
+            ));
         }
+
+        Ok((_identifier, _event))
     }
 
     // This method is used to get an event
@@ -320,7 +2215,7 @@ impl Store {
                 Err(err) => Err(err),
                 // If the event is found, we check if the event belongs to the group
                 Ok((_identifier, event)) => {
-                    if event.is_deleted {
+                    if event.is_deleted || event.is_pending {
                         return Err(api_error(
                             ApiErrorType::NotFound,
                             "EVENT_NOT_FOUND",
@@ -364,21 +2259,44 @@ impl Store {
     }
 
     // This method is used to get the events for a group filtered, sorted and paginated
+    // optionally with the facets counted over all the events that matched the filter
     pub fn get_events(
         limit: usize,
         page: usize,
-        sort: EventSort,
-        filters: Vec<EventFilter>,
-        filter_type: FilterType,
+        sort: Vec<EventSort>,
+        filter: EventFilterExpression,
+        group_identifier: Option<Principal>,
+        facets: Option<EventFacetRequest>,
+    ) -> PagedEventsResponse {
+        let (filtered_events, facets) =
+            Self::get_filtered_events(group_identifier, &filter, facets.as_ref());
+
+        // Sort the events
+        let ordered_events = sort_events(filtered_events, &sort);
+
+        PagedEventsResponse {
+            events: get_paged_data(ordered_events, limit, page),
+            facets,
+        }
+    }
+
+    // Method to get the (not deleted) events of a group that match the filter expression
+    // the requested facets are counted in the same pass
+    fn get_filtered_events(
         group_identifier: Option<Principal>,
-    ) -> PagedResponse<EventResponse> {
+        filter: &EventFilterExpression,
+        facets: Option<&EventFacetRequest>,
+    ) -> (Vec<EventResponse>, Option<EventFacets>) {
+        let now = time();
+        let mut facet_counter = facets.map(EventFacetCounter::new);
+
         // Get all the events
         let entries = ENTRIES.with(|entries| Data::get_entries(entries));
 
         // Filter the events by group identifier
-        let events: Vec<EventResponse> = entries
+        let filtered_events: Vec<EventResponse> = entries
             .into_iter()
-            .filter(|(_, _event)| !_event.is_deleted)
+            .filter(|(_, _event)| !_event.is_deleted && !_event.is_pending)
             .filter(|(_, _event)| {
                 if let Some(_group_identifier) = group_identifier {
                     return &_event.group_identifier == &_group_identifier;
@@ -386,15 +2304,19 @@ impl Store {
                 true
             })
             .map(|(id, event)| Self::map_to_event_response(id, event))
+            // Filter the events by the filter expression
+            .filter(|event| filter.matches(event, now))
+            .inspect(|event| {
+                if let Some(counter) = facet_counter.as_mut() {
+                    counter.add(event);
+                }
+            })
             .collect();
 
-        // Filter the events by the filters
-        let filtered_events = Self::get_filtered_events(events, filters, filter_type);
-
-        // Sort the events
-        let ordered_events = Self::get_ordered_events(filtered_events, sort);
-
-        get_paged_data(ordered_events, limit, page)
+        (
+            filtered_events,
+            facet_counter.map(|counter| counter.into_facets()),
+        )
     }
 
     // This method is used to get the events count for a set of groups
@@ -408,69 +2330,209 @@ impl Store {
 
         ENTRIES.with(|entries| {
             // For each group, we count the number of events
-            entries.borrow().iter().for_each(|(_, event)| {
-                if let Some(existing_value) = events_counts.get_mut(&event.group_identifier) {
-                    *existing_value += 1;
-                }
-            });
+            entries
+                .borrow()
+                .iter()
+                .filter(|(_, event)| !event.is_pending)
+                .for_each(|(_, event)| {
+                    if let Some(existing_value) = events_counts.get_mut(&event.group_identifier) {
+                        *existing_value += 1;
+                    }
+                });
+        });
+
+        events_counts.into_iter().collect()
+    }
+
+    // This method is used to get the events count for a set of groups split into upcoming, past and cancelled events
+    // deleted events are not counted, events that started but didnt end yet are only part of the total
+    pub fn get_events_count_breakdown(
+        group_identifiers: Vec<Principal>,
+    ) -> Vec<(Principal, EventsCount)> {
+        let now = time();
+        let mut events_counts: HashMap<Principal, EventsCount> =
+            HashMap::from_iter(group_identifiers.iter().map(|group_identifier| {
+                (
+                    *group_identifier,
+                    EventsCount {
+                        total: 0,
+                        breakdown: Some(EventsCountBreakdown::default()),
+                    },
+                )
+            }));
+
+        ENTRIES.with(|entries| {
+            entries
+                .borrow()
+                .iter()
+                .filter(|(_, event)| !event.is_deleted && !event.is_pending)
+                .for_each(|(_, event)| {
+                    if let Some(existing_value) = events_counts.get_mut(&event.group_identifier) {
+                        existing_value.total += 1;
+
+                        if let Some(breakdown) = existing_value.breakdown.as_mut() {
+                            if event.is_canceled.0 {
+                                breakdown.cancelled += 1;
+                            } else if event.date.start_date > now {
+                                breakdown.upcoming += 1;
+                            } else if event.date.end_date > 0 && event.date.end_date < now {
+                                breakdown.past += 1;
+                            }
+                        }
+                    }
+                });
         });
 
         events_counts.into_iter().collect()
     }
 
+    // This method is used to get the amount of live (not deleted or cancelled) events per tag for a group
+    pub fn get_tag_counts(group_identifier: Principal) -> Vec<(u32, usize)> {
+        let mut tag_counts: HashMap<u32, usize> = HashMap::new();
+
+        ENTRIES.with(|entries| {
+            entries
+                .borrow()
+                .iter()
+                .filter(|(_, event)| {
+                    event.group_identifier == group_identifier
+                        && !event.is_deleted
+                        && !event.is_pending
+                        && !event.is_canceled.0
+                })
+                .for_each(|(_, event)| {
+                    // Count every tag once per event
+                    let tags: HashSet<u32> = event.tags.into_iter().collect();
+                    for tag in tags {
+                        *tag_counts.entry(tag).or_insert(0) += 1;
+                    }
+                });
+        });
+
+        tag_counts.into_iter().collect()
+    }
+
     // Used for composite_query calls from the parent canister
     //
     // Method to get filtered events serialized and chunked
+    // only the first `max_entries` events in the requested order are returned, the parent canister merges the events of all the child canisters
+    //
+    // The first call (without snapshot) creates the snapshot handle that is passed along with the next chunk calls.
+    // Queries cant keep state between calls, so the events are serialized again for every chunk and checked against the snapshot,
+    // if the data changed in the meantime or the snapshot is expired an error is returned instead of bytes that dont fit together
     pub fn get_chunked_data(
-        filters: Vec<EventFilter>,
-        filter_type: FilterType,
+        request: ChunkedDataRequest,
+        snapshot: Option<ChunkSnapshot>,
         chunk: usize,
         max_bytes_per_chunk: usize,
-    ) -> (Vec<u8>, (usize, usize)) {
-        // Get all the events
-        let events = ENTRIES.with(|entries| Data::get_entries(entries));
-        // Filter out deleted events and map the events to EventResponse
-        let mapped_events: Vec<EventResponse> = events
-            .iter()
-            .filter(|(_identifier, _event_data)| !_event_data.is_deleted)
-            .map(|(_identifier, _event_data)| {
-                Self::map_to_event_response(_identifier.clone(), _event_data.clone())
-            })
+    ) -> Result<ChunkedData, ApiError> {
+        let name = STABLE_DATA.with(|data| Data::get_name(data.borrow().get()));
+        let data_version = DATA_VERSION.with(|version| *version.borrow());
+        let now = time();
+        let inputs = Some(vec![format!("chunk - {}", &chunk)]);
+
+        if let Some(_snapshot) = &snapshot {
+            if now.saturating_sub(_snapshot.created_on) > CHUNK_SNAPSHOT_TTL
+                || _snapshot.id != data_version
+            {
+                return Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "SNAPSHOT_EXPIRED",
+                    "The snapshot is expired, request the first chunk again",
+                    name.as_str(),
+                    "get_chunked_data",
+                    inputs,
+                ));
+            }
+        }
+
+        // Get the events of the group that match the filter expression specified in the request
+        let (filtered_events, facets) = Self::get_filtered_events(
+            request.group_identifier,
+            &request.filter,
+            request.facets.as_ref(),
+        );
+        let total = filtered_events.len();
+
+        // Sort the events and only keep the events that can be on the requested page
+        let events: Vec<EventResponse> = sort_events(filtered_events, &request.sort)
+            .into_iter()
+            .take(request.max_entries)
             .collect();
 
-        // Filter the events by the filters specified in the method arguments
-        let filtered_events = Self::get_filtered_events(mapped_events, filters, filter_type);
         // Serialize the events
-        if let Ok(bytes) = serialize(&filtered_events) {
-            // Check if the bytes of the serialized events are greater than the max bytes per chunk specified as an argument
-            if bytes.len() >= max_bytes_per_chunk {
-                // Get the start and end index of the bytes to be returned
-                let start = chunk * max_bytes_per_chunk;
-                let end = (chunk + 1) * (max_bytes_per_chunk);
-
-                // Get the bytes to be returned, if the end index is greater than the length of the bytes, return the remaining bytes
-                let response = if end >= bytes.len() {
-                    bytes[start..].to_vec()
-                } else {
-                    bytes[start..end].to_vec()
-                };
+        let bytes = match serialize(&EventsSlice {
+            total,
+            events,
+            facets,
+        }) {
+            Ok(_bytes) => _bytes,
+            Err(_) => {
+                return Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "SERIALIZE_FAILED",
+                    "The events cant be serialized",
+                    name.as_str(),
+                    "get_chunked_data",
+                    inputs,
+                ))
+            }
+        };
 
-                // Determine the max number of chunks that can be returned, a float is used because the number of chunks can be a decimal in this step
-                let mut max_chunks: f64 = 0.00;
-                if max_bytes_per_chunk < bytes.len() {
-                    max_chunks = (bytes.len() / max_bytes_per_chunk) as f64;
+        let checksum = ChunkSnapshot::checksum(&bytes);
+        let snapshot = match snapshot {
+            // Check if the serialized events still match the snapshot
+            Some(_snapshot) => {
+                if _snapshot.total_bytes != bytes.len() || _snapshot.checksum != checksum {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "SNAPSHOT_EXPIRED",
+                        "The data changed since the snapshot was created, request the first chunk again",
+                        name.as_str(),
+                        "get_chunked_data",
+                        inputs,
+                    ));
                 }
-
-                // return the response and start and end chunk index, the end chunk index is calculated by rounding up the max chunks
-                return (response, (chunk, max_chunks.ceil() as usize));
+                _snapshot
             }
+            None => ChunkSnapshot {
+                id: data_version,
+                total_bytes: bytes.len(),
+                checksum,
+                created_on: now,
+            },
+        };
+
+        // Determine the number of chunks, there is always at least one (possibly empty) chunk
+        let max_bytes_per_chunk = max_bytes_per_chunk.max(1);
+        let total_chunks = bytes.len().div_ceil(max_bytes_per_chunk).max(1);
 
-            // if the bytes of the serialized groups are less than the max bytes per chunk specified as an argument, return the bytes and start and end chunk index as 0
-            return (bytes, (0, 0));
-        } else {
-            // if the groups cant be serialized return an empty vec and start and end chunk index as 0
-            return (vec![], (0, 0));
+        if chunk >= total_chunks {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "CHUNK_OUT_OF_RANGE",
+                "The requested chunk doesnt exist",
+                name.as_str(),
+                "get_chunked_data",
+                inputs,
+            ));
         }
+
+        // Get the bytes of the requested chunk, the last chunk contains the remaining bytes
+        let start = (chunk * max_bytes_per_chunk).min(bytes.len());
+        let end = ((chunk + 1) * max_bytes_per_chunk).min(bytes.len());
+
+        Ok(ChunkedData {
+            snapshot,
+            chunk,
+            total_chunks,
+            bytes: bytes[start..end].to_vec(),
+        })
+    }
+
+    // This method is used to change the data version after every write, chunk snapshots of an older version are expired
+    pub fn update_data_version() {
+        DATA_VERSION.with(|version| *version.borrow_mut() = time());
     }
 
     // Method to map events to a default reponse that can be used on the frontend
@@ -499,263 +2561,279 @@ impl Store {
             is_canceled: event.is_canceled,
             is_deleted: event.is_deleted,
             metadata: event.metadata,
+            typed_metadata: event.typed_metadata,
+            hosts: event.hosts,
+            version: event.version,
+            ticket_tiers: event
+                .ticket_tiers
+                .iter()
+                .map(|tier| tier.to_response(time()))
+                .collect(),
+            tickets_sold: event.ticket_tiers.iter().map(|tier| tier.sold).sum(),
+            sessions: event.sessions,
+            speakers: event.speakers,
+            sponsors: event.sponsors,
             group_identifier: event.group_identifier,
         }
     }
 
-    // Method to filter events
-    fn get_filtered_events(
-        events: Vec<EventResponse>,
-        filters: Vec<EventFilter>,
-        filter_type: FilterType,
-    ) -> Vec<EventResponse> {
-        if let FilterType::Or = filter_type {
-            if filters.len() == 0 {
-                return events;
+    // This method is used to get the metadata schema of a group
+    pub fn get_metadata_schema(group_identifier: Principal) -> Option<MetadataSchema> {
+        METADATA_SCHEMAS.with(|schemas| schemas.borrow().get(&group_identifier.to_string()))
+    }
+
+    // This method is used to register the metadata schema of a group, an existing schema is replaced
+    // the schema is registered on the parent canister which pushes it to all the child canisters (inter-canister call)
+    pub async fn set_metadata_schema(
+        caller: Principal,
+        group_identifier: Principal,
+        fields: Vec<MetadataField>,
+    ) -> Result<MetadataSchema, ApiError> {
+        let schema = MetadataSchema {
+            group_identifier,
+            fields,
+            updated_by: caller,
+            updated_on: time(),
+        };
+        validate_metadata_schema(&schema)?;
+
+        let parent = STABLE_DATA.with(|data| data.borrow().get().parent);
+        let result: Result<(Result<(), ApiError>,), _> =
+            call::call(parent, "register_metadata_schema", (schema.clone(),)).await;
+
+        match result {
+            Ok((Ok(_),)) => {
+                // The parent pushes the schema to this canister as well, it is stored directly so it can be used right away
+                Self::store_metadata_schema(schema.clone());
+                Ok(schema)
             }
+            Ok((Err(err),)) => Err(err),
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_REGISTER_SCHEMA",
+                err.1.as_str(),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "set_metadata_schema",
+                Some(vec![format!(
+                    "group_identifier - {}",
+                    &group_identifier.to_string()
+                )]),
+            )),
         }
+    }
+
+    // This method is used to store a metadata schema pushed by the parent canister
+    pub fn store_metadata_schema(schema: MetadataSchema) {
+        METADATA_SCHEMAS.with(|schemas| {
+            schemas
+                .borrow_mut()
+                .insert(schema.group_identifier.to_string(), schema)
+        });
+    }
+
+    // This method is used to get the groups that have events stored on this canister
+    pub fn get_group_identifiers() -> Vec<Principal> {
+        let group_identifiers: HashSet<Principal> = ENTRIES.with(|entries| {
+            entries
+                .borrow()
+                .iter()
+                .map(|(_, event)| event.group_identifier)
+                .collect()
+        });
+
+        group_identifiers.into_iter().collect()
+    }
+
+    // Used for event migrations orchestrated by the parent canister
+    //
+    // This method is used to get the identifiers of all the events of a group, including deleted events
+    pub fn get_migration_identifiers(group_identifier: Principal) -> Vec<Principal> {
+        ENTRIES.with(|entries| {
+            entries
+                .borrow()
+                .iter()
+                .filter(|(_, event)| event.group_identifier == group_identifier)
+                .filter_map(|(identifier, _)| Principal::from_text(identifier).ok())
+                .collect()
+        })
+    }
+
+    // This method is used to get the stored events for a list of identifiers, unknown identifiers are skipped
+    pub fn get_migration_entries(identifiers: Vec<Principal>) -> Vec<(Principal, Event)> {
+        ENTRIES.with(|entries| {
+            identifiers
+                .into_iter()
+                .filter_map(|identifier| {
+                    entries
+                        .borrow()
+                        .get(&identifier.to_string())
+                        .map(|event| (identifier, event))
+                })
+                .collect()
+        })
+    }
 
-        // filter out deleted events
-        let mut filtered_events: Vec<EventResponse> = events
+    // This method is used to get the identifiers of the events that can't be moved because they hold ticket payments
+    // the payments are held on the subaccount of the event on this canister, so the events have to stay on this canister
+    pub fn get_migration_blocked_identifiers(identifiers: Vec<Principal>) -> Vec<Principal> {
+        identifiers
             .into_iter()
-            .filter(|event| !event.is_deleted)
-            .collect();
+            .filter(|identifier| !Self::get_ticket_payment_keys(*identifier).is_empty())
+            .collect()
+    }
 
-        use FilterType::*;
-        match filter_type {
-            // this filter type will return events that match all the filters
-            And => {
-                for filter in filters {
-                    use EventFilter::*;
-                    match filter {
-                        Identifiers(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| value.contains(&event.identifier))
-                                .collect();
-                        }
-                        Name(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| event.name.contains(&value))
-                                .collect();
-                        }
-                        StartDate(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.date.start_date >= value.start_date
-                                            && event.date.start_date <= value.end_date;
-                                    } else {
-                                        return event.date.start_date >= value.start_date;
-                                    }
-                                })
-                                .collect()
-                        }
-                        EndDate(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.date.end_date >= value.start_date
-                                            && event.date.end_date <= value.end_date;
-                                    } else {
-                                        return event.date.end_date >= value.start_date;
-                                    }
-                                })
-                                .collect();
-                        }
-                        Owner(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| event.owner == value)
-                                .collect();
-                        }
-                        Tag(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| event.tags.contains(&value))
-                                .collect();
-                        }
-                        UpdatedOn(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.updated_on >= value.start_date
-                                            && event.updated_on <= value.end_date;
-                                    } else {
-                                        return event.updated_on >= value.start_date;
-                                    }
-                                })
-                                .collect();
-                        }
-                        CreatedOn(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.created_on >= value.start_date
-                                            && event.created_on <= value.end_date;
-                                    } else {
-                                        return event.created_on >= value.start_date;
-                                    }
-                                })
-                                .collect();
-                        }
-                        IsCanceled(value) => {
-                            filtered_events = filtered_events
-                                .into_iter()
-                                .filter(|event| value == event.is_canceled.0)
-                                .collect();
-                        }
+    // This method is used to store events that are moved from a sibling canister, the identifiers are preserved
+    pub fn import_migration_entries(entries: Vec<(Principal, Event)>) -> Result<(), ApiError> {
+        // Check if all the identifiers are event identifiers before storing anything
+        for (identifier, _) in entries.iter() {
+            let (_, _, _kind) = Identifier::decode(identifier);
+            if IDENTIFIER_KIND != _kind {
+                return Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "INVALID_IDENTIFIER",
+                    "The identifier is not an event identifier",
+                    STABLE_DATA
+                        .with(|data| Data::get_name(data.borrow().get()))
+                        .as_str(),
+                    "import_migration_entries",
+                    Some(vec![format!("identifier - {}", identifier.to_string())]),
+                ));
+            }
+        }
+
+        STABLE_DATA.with(|data| {
+            ENTRIES.with(|_entries| {
+                // Check if all the events fit before storing anything, so a batch is never partially imported
+                let entries_count = _entries.borrow().len();
+                if entries_count + entries.len() as u64 > Data::get_max_entries(_entries) {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "CANISTER_AT_CAPACITY",
+                        "The events do not fit on this canister",
+                        Data::get_name(data.borrow().get()).as_str(),
+                        "import_migration_entries",
+                        Some(vec![format!("entries - {}", entries.len())]),
+                    ));
+                }
+
+                for (identifier, event) in entries {
+                    Data::add_entry_by_principal(data, _entries, event, identifier)?;
+
+                    // Identifiers issued by this canister (an event that is moved back) are kept ahead of the entry counter,
+                    // so new events never get an identifier that is already in use
+                    let (_id, _canister, _) = Identifier::decode(&identifier);
+                    let mut _data = data.borrow().get().clone();
+                    if _canister == id() && _data.current_entry_id <= _id {
+                        _data.current_entry_id = _id + 1;
+                        let _ = data.borrow_mut().set(_data);
                     }
                 }
+                Ok(())
+            })
+        })?;
+        Self::update_data_version();
 
-                filtered_events
-            }
-            // This filter type will return events that match any of the filters
-            Or => {
-                let mut hashmap_events: HashMap<Principal, EventResponse> = HashMap::new();
-                for filter in filters {
-                    use EventFilter::*;
-                    match filter {
-                        Identifiers(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| value.contains(&event.identifier))
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        Name(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| event.name.contains(&value))
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        StartDate(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.date.start_date >= value.start_date
-                                            && event.date.start_date <= value.end_date;
-                                    } else {
-                                        return event.date.start_date >= value.start_date;
-                                    }
-                                })
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        EndDate(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.date.end_date >= value.start_date
-                                            && event.date.end_date <= value.end_date;
-                                    } else {
-                                        return event.date.end_date >= value.start_date;
-                                    }
-                                })
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        Owner(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| event.owner == value)
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        Tag(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| event.tags.contains(&value))
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        UpdatedOn(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.updated_on >= value.start_date
-                                            && event.updated_on <= value.end_date;
-                                    } else {
-                                        return event.updated_on >= value.start_date;
-                                    }
-                                })
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
-                        }
-                        CreatedOn(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| {
-                                    if value.end_date > 0 {
-                                        return event.created_on >= value.start_date
-                                            && event.created_on <= value.end_date;
-                                    } else {
-                                        return event.created_on >= value.start_date;
-                                    }
-                                })
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
+        Ok(())
+    }
+
+    // This method is used to remove events that are moved to a sibling canister
+    // an event is only removed when it didn't change since it was copied, the removed identifiers are returned
+    pub fn remove_migration_entries(entries: Vec<(Principal, String)>) -> Vec<Principal> {
+        Self::update_data_version();
+        ENTRIES.with(|_entries| {
+            entries
+                .into_iter()
+                .filter(|(identifier, fingerprint)| {
+                    let key = identifier.to_string();
+                    let removed = match _entries.borrow().get(&key) {
+                        Some(event)
+                            if &event.fingerprint() == fingerprint
+                                && Self::get_ticket_payment_keys(*identifier).is_empty() =>
+                        {
+                            Some(event)
                         }
-                        IsCanceled(value) => {
-                            filtered_events
-                                .iter()
-                                .filter(|event| value == event.is_canceled.0)
-                                .for_each(|v| {
-                                    hashmap_events.insert(v.identifier.clone(), v.clone());
-                                });
+                        _ => None,
+                    };
+
+                    match removed {
+                        Some(event) => {
+                            _entries.borrow_mut().remove(&key);
+                            // The parent removes this canister for the group once all its events are moved,
+                            // so the group is registered again when a new event of the group is stored
+                            REGISTERED_GROUPS
+                                .with(|groups| groups.borrow_mut().remove(&event.group_identifier));
+                            true
                         }
+                        None => false,
                     }
+                })
+                .map(|(identifier, _)| identifier)
+                .collect()
+        })
+    }
+
+    // This method is used to remove the copies of events that stay on the source canister because they changed while being moved
+    pub fn discard_migration_entries(identifiers: Vec<Principal>) {
+        ENTRIES.with(|entries| {
+            identifiers.iter().for_each(|identifier| {
+                entries.borrow_mut().remove(&identifier.to_string());
+            })
+        });
+        Self::update_data_version();
+    }
+
+    // This method is used to get the check-ins of events that are moved to a sibling canister
+    pub fn get_migration_check_ins(identifiers: Vec<Principal>) -> Vec<CheckIn> {
+        CHECK_INS.with(|check_ins| {
+            identifiers
+                .iter()
+                .flat_map(|identifier| {
+                    let prefix = format!("{}-", identifier);
+                    check_ins
+                        .borrow()
+                        .range(prefix.clone()..)
+                        .take_while(|(key, _)| key.starts_with(&prefix))
+                        .map(|(_, check_in)| check_in)
+                        .collect::<Vec<CheckIn>>()
+                })
+                .collect()
+        })
+    }
+
+    // This method is used to store the check-ins of events moved from a sibling canister, existing check-ins are kept
+    pub fn import_migration_check_ins(check_ins: Vec<CheckIn>) {
+        CHECK_INS.with(|_check_ins| {
+            for check_in in check_ins {
+                let key = format!("{}-{}", check_in.event_identifier, check_in.attendee);
+                if !_check_ins.borrow().contains_key(&key) {
+                    _check_ins.borrow_mut().insert(key, check_in);
                 }
-                hashmap_events.into_iter().map(|v| v.1).collect()
             }
-        }
+        });
     }
 
-    // This function will sort the events based on the sort type
-    fn get_ordered_events(mut events: Vec<EventResponse>, sort: EventSort) -> Vec<EventResponse> {
-        use EventSort::*;
-        use SortDirection::*;
-        match sort {
-            CreatedOn(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.created_on.cmp(&b.created_on)),
-                Desc => events.sort_by(|a, b| b.created_on.cmp(&a.created_on)),
-            },
-            UpdatedOn(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.updated_on.cmp(&b.updated_on)),
-                Desc => events.sort_by(|a, b| b.updated_on.cmp(&a.updated_on)),
-            },
-            StartDate(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.date.start_date.cmp(&b.date.start_date)),
-                Desc => events.sort_by(|a, b| b.date.start_date.cmp(&a.date.start_date)),
-            },
-            EndDate(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.date.end_date.cmp(&b.date.end_date)),
-                Desc => events.sort_by(|a, b| b.date.end_date.cmp(&a.date.end_date)),
-            },
-            AttendeeCount(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.attendee_count.cmp(&b.attendee_count)),
-                Desc => events.sort_by(|a, b| b.attendee_count.cmp(&a.attendee_count)),
-            },
-        };
+    // This method is used to remove the check-ins of events that are moved to a sibling canister
+    // check-ins of events that are still stored on this canister are kept
+    pub fn remove_migration_check_ins(identifiers: Vec<Principal>) {
+        let keys: Vec<String> = Self::get_migration_check_ins(
+            identifiers
+                .into_iter()
+                .filter(|identifier| {
+                    !ENTRIES.with(|entries| entries.borrow().contains_key(&identifier.to_string()))
+                })
+                .collect(),
+        )
+        .iter()
+        .map(|check_in| format!("{}-{}", check_in.event_identifier, check_in.attendee))
+        .collect();
 
-        events
+        CHECK_INS.with(|check_ins| {
+            keys.iter().for_each(|key| {
+                check_ins.borrow_mut().remove(key);
+            })
+        });
     }
 
     pub fn update_attendee_count_on_event(
@@ -781,6 +2859,7 @@ impl Store {
                             .attendee_count
                             .insert(event_attendee_canister, attendee_count);
                         let _ = Data::update_entry(data, entries, event_identifier, _event);
+                        Self::update_data_version();
                         Ok(())
                     }
                     Err(_) => Err(false),
@@ -789,6 +2868,22 @@ impl Store {
         })
     }
 
+    // This method is used for role / permission based access control
+    // the metadata schema can only be changed by members that can edit all the events of the group
+    pub async fn can_manage_metadata_schema(
+        caller: Principal,
+        group_identifier: Principal,
+        member_identifier: Principal,
+    ) -> Result<Principal, ApiError> {
+        Self::check_permission(
+            caller,
+            group_identifier,
+            member_identifier,
+            PermissionActionType::Edit,
+        )
+        .await
+    }
+
     // This method is used for role / permission based access control
     pub async fn can_write(
         caller: Principal,
@@ -820,16 +2915,24 @@ impl Store {
     }
 
     // This method is used for role / permission based access control
+    // hosts of the event are allowed when they have the ability, without an ability only the owner and group roles are checked
     pub async fn can_edit(
         caller: Principal,
         event_identifier: Principal,
         group_identifier: Principal,
         member_identifier: Principal,
+        ability: Option<EventHostAbility>,
     ) -> Result<Principal, ApiError> {
         if let Ok(event) = Self::get_event(event_identifier, Some(group_identifier)) {
             if event.owner == caller {
                 return Ok(caller);
             }
+
+            if let Some(_ability) = ability {
+                if EventHost::has_ability(&event.hosts, &caller, &_ability) {
+                    return Ok(caller);
+                }
+            }
         }
 
         return Self::check_permission(
@@ -849,7 +2952,7 @@ impl Store {
         member_identifier: Principal,
     ) -> Result<Principal, ApiError> {
         if let Ok(event) = Self::get_event(event_identifier, Some(group_identifier)) {
-            if event.owner == caller {
+            if event.owner == caller || EventHost::can_delete(&event.hosts, &caller) {
                 return Ok(caller);
             }
         }
@@ -869,8 +2972,16 @@ impl Store {
         member_identifier: Principal,
         permission: PermissionActionType,
     ) -> Result<Principal, ApiError> {
-        let group_roles = get_group_roles(group_identifier).await;
-        let member_roles = get_member_roles(member_identifier, group_identifier).await;
+        let group_roles = Self::get_cached_roles(
+            RoleCacheKey::Group(group_identifier),
+            get_group_roles(group_identifier),
+        )
+        .await;
+        let member_roles = Self::get_cached_roles(
+            RoleCacheKey::Member(member_identifier, group_identifier),
+            get_member_roles(member_identifier, group_identifier),
+        )
+        .await;
         let name = STABLE_DATA
             .with(|data| Data::get_name(data.borrow().get()))
             .to_string();
@@ -932,6 +3043,163 @@ impl Store {
         }
     }
 
+    // This method is used to get roles from the cache, the roles are fetched and cached when they are missing or expired
+    async fn get_cached_roles<T: CandidType + DeserializeOwned>(
+        key: RoleCacheKey,
+        fetch: impl Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let cached = ROLE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            match cache.get(&key) {
+                Some((expires_at, bytes)) if *expires_at > time() => Some(bytes.clone()),
+                // Expired roles are removed when they are read
+                Some(_) => {
+                    cache.remove(&key);
+                    None
+                }
+                None => None,
+            }
+        });
+
+        if let Some(bytes) = cached {
+            if let Ok(roles) = decode_one::<T>(&bytes) {
+                return Ok(roles);
+            }
+        }
+
+        let generation = Self::get_role_cache_generation(&key);
+        let roles = fetch.await?;
+
+        // The roles are invalidated during the fetch, so they could already be stale
+        if generation != Self::get_role_cache_generation(&key) {
+            return Ok(roles);
+        }
+
+        if let Ok(bytes) = encode_one(&roles) {
+            ROLE_CACHE.with(|cache| {
+                let mut cache = cache.borrow_mut();
+                let now = time();
+                cache.retain(|_, (expires_at, _)| *expires_at > now);
+
+                if cache.len() >= MAX_ROLE_CACHE_ENTRIES && !cache.contains_key(&key) {
+                    let first_expiring = cache
+                        .iter()
+                        .min_by_key(|(_, (expires_at, _))| *expires_at)
+                        .map(|(key, _)| key.clone());
+                    if let Some(_first_expiring) = first_expiring {
+                        cache.remove(&_first_expiring);
+                    }
+                }
+
+                cache.insert(key, (now + ROLE_CACHE_TTL, bytes))
+            });
+        }
+
+        Ok(roles)
+    }
+
+    // The generation of the cached roles, the member roles are also invalidated with the roles of their group
+    fn get_role_cache_generation(key: &RoleCacheKey) -> (u64, u64) {
+        let group_key = match key {
+            RoleCacheKey::Group(_group_identifier) | RoleCacheKey::Member(_, _group_identifier) => {
+                RoleCacheKey::Group(*_group_identifier)
+            }
+        };
+
+        ROLE_CACHE_GENERATIONS.with(|generations| {
+            let generations = generations.borrow();
+            (
+                generations.get(&group_key).cloned().unwrap_or_default(),
+                generations.get(key).cloned().unwrap_or_default(),
+            )
+        })
+    }
+
+    // This method is used to remove cached roles when they changed on the group or member canister
+    // without a member the group roles and the roles of all the members of the group are removed
+    // the caller must be the canister of the group identifier, or the canister of the member identifier for its own member roles
+    pub fn invalidate_role_cache(
+        caller: Principal,
+        group_identifier: Principal,
+        member_identifier: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let (_, group_canister, _) = Identifier::decode(&group_identifier);
+        let is_member_canister = member_identifier.is_some_and(|_member_identifier| {
+            let (_, member_canister, _) = Identifier::decode(&_member_identifier);
+            member_canister == caller
+        });
+
+        if caller != group_canister && !is_member_canister {
+            return Err(api_error(
+                ApiErrorType::Unauthorized,
+                "UNAUTHORIZED",
+                "Only the group or member canister can invalidate the roles",
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "invalidate_role_cache",
+                None,
+            ));
+        }
+
+        let generation_key = match member_identifier {
+            Some(_member_identifier) => RoleCacheKey::Member(_member_identifier, group_identifier),
+            None => RoleCacheKey::Group(group_identifier),
+        };
+        ROLE_CACHE_GENERATIONS
+            .with(|generations| *generations.borrow_mut().entry(generation_key).or_default() += 1);
+
+        ROLE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            match member_identifier {
+                Some(_member_identifier) => {
+                    cache.remove(&RoleCacheKey::Member(_member_identifier, group_identifier));
+                }
+                None => cache.retain(|key, _| match key {
+                    RoleCacheKey::Group(_group_identifier)
+                    | RoleCacheKey::Member(_, _group_identifier) => {
+                        _group_identifier != &group_identifier
+                    }
+                }),
+            }
+        });
+
+        Ok(())
+    }
+
+    // Let the parent canister know that this canister stores events of the group (inter-canister call)
+    // the parent uses this to place new events of a group on the same canister and to query only these canisters
+    async fn register_group_on_parent(group_identifier: Principal) -> Result<(), ApiError> {
+        if REGISTERED_GROUPS.with(|groups| groups.borrow().contains(&group_identifier)) {
+            return Ok(());
+        }
+
+        let parent = STABLE_DATA.with(|data| data.borrow().get().parent);
+        let result: Result<(Result<(), ApiError>,), _> =
+            call::call(parent, "register_group_canister", (group_identifier,)).await;
+
+        match result {
+            Ok((Ok(_),)) => {
+                REGISTERED_GROUPS.with(|groups| groups.borrow_mut().insert(group_identifier));
+                Ok(())
+            }
+            Ok((Err(err),)) => Err(err),
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "GROUP_REGISTRATION_FAILED",
+                err.1.as_str(),
+                STABLE_DATA
+                    .with(|data| Data::get_name(data.borrow().get()))
+                    .as_str(),
+                "register_group_on_parent",
+                Some(vec![format!(
+                    "group_identifier - {}",
+                    &group_identifier.to_string()
+                )]),
+            )),
+        }
+    }
+
     // Add the owner as an attendee to the event attendee canister (inter-canister call)
     async fn add_owner_as_attendee(
         user_principal: &Principal,
@@ -1,53 +1,1002 @@
+use ic_cdk::api::time;
 use ic_scalable_canister::ic_scalable_misc::{
-    enums::{api_error_type::ApiError, validation_type::ValidationType},
+    enums::{api_error_type::ApiError, location_type::Location, validation_type::ValidationType},
     helpers::validation_helper::Validator,
-    models::validation_models::ValidateField,
+    models::{
+        date_models::DateRange,
+        validation_models::{ValidateField, ValidationResponse},
+    },
 };
 
-use shared::event_models::{PostEvent, UpdateEvent};
+use shared::event_models::{
+    EventPatch, EventSession, MetadataSchema, MetadataValue, PostEvent, PostEventProfile,
+    PostEventSession, PostTicketTier, SessionLocation, UpdateEvent,
+};
+use std::collections::HashMap;
 
-pub fn validate_post_event(post_event: PostEvent) -> Result<(), ApiError> {
-    let validator_fields = vec![
-        ValidateField(
-            ValidationType::StringLength(post_event.name, 3, 64),
+// Events can't start more than a year before they are stored or their start date is changed
+pub static MAX_EVENT_AGE: u64 = 365 * 24 * 60 * 60 * 1_000_000_000;
+// The maximum size of the metadata in bytes
+pub static MAX_METADATA_BYTES: usize = 10_000;
+// The maximum amount of typed metadata keys per event
+pub static MAX_TYPED_METADATA_KEYS: usize = 50;
+// The maximum length of a typed metadata key and a text or url value
+pub static MAX_TYPED_METADATA_KEY_LENGTH: usize = 64;
+pub static MAX_TYPED_METADATA_VALUE_LENGTH: usize = 1_000;
+
+// The fields of an event that are validated, used by the add, edit and patch validation
+// fields that are not set are not validated
+#[derive(Default)]
+struct EventFields<'a> {
+    name: Option<&'a str>,
+    description: Option<&'a str>,
+    website: Option<&'a str>,
+    tags: Option<&'a [u32]>,
+    date: Option<&'a DateRange>,
+    location: Option<&'a Location>,
+    metadata: Option<&'a String>,
+    typed_metadata: Option<&'a HashMap<String, MetadataValue>>,
+    // The metadata schema of the group, only used when the typed metadata is set
+    schema: Option<&'a MetadataSchema>,
+    // The stored date of the event, the age of the start date is only validated when it changes
+    current_date: Option<&'a DateRange>,
+}
+
+pub fn validate_post_event(
+    post_event: PostEvent,
+    schema: Option<&MetadataSchema>,
+) -> Result<(), ApiError> {
+    validate_event_fields(
+        EventFields {
+            name: Some(&post_event.name),
+            description: Some(&post_event.description),
+            website: Some(&post_event.website),
+            tags: Some(&post_event.tags),
+            date: Some(&post_event.date),
+            location: Some(&post_event.location),
+            metadata: post_event.metadata.as_ref(),
+            typed_metadata: Some(&post_event.typed_metadata),
+            schema,
+            current_date: None,
+        },
+        time(),
+    )
+}
+
+pub fn validate_update_event(
+    update_event: UpdateEvent,
+    current_date: &DateRange,
+    schema: Option<&MetadataSchema>,
+) -> Result<(), ApiError> {
+    validate_event_fields(
+        EventFields {
+            name: Some(&update_event.name),
+            description: Some(&update_event.description),
+            website: Some(&update_event.website),
+            tags: Some(&update_event.tags),
+            date: Some(&update_event.date),
+            location: Some(&update_event.location),
+            metadata: update_event.metadata.as_ref(),
+            typed_metadata: Some(&update_event.typed_metadata),
+            schema,
+            current_date: Some(current_date),
+        },
+        time(),
+    )
+}
+
+// Only the fields that are supplied in the patch are validated
+pub fn validate_event_patch(
+    patch: &EventPatch,
+    current_date: &DateRange,
+    schema: Option<&MetadataSchema>,
+) -> Result<(), ApiError> {
+    validate_event_fields(
+        EventFields {
+            name: patch.name.as_deref(),
+            description: patch.description.as_deref(),
+            website: patch.website.as_deref(),
+            tags: patch.tags.as_deref(),
+            date: patch.date.as_ref(),
+            location: patch.location.as_ref(),
+            metadata: patch
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.as_ref()),
+            typed_metadata: patch.typed_metadata.as_ref(),
+            schema,
+            current_date: Some(current_date),
+        },
+        time(),
+    )
+}
+
+// Validate the event fields, all the invalid fields are returned at once
+fn validate_event_fields(fields: EventFields, now: u64) -> Result<(), ApiError> {
+    let mut validator_fields = vec![];
+    if let Some(name) = fields.name {
+        validator_fields.push(ValidateField(
+            ValidationType::StringLength(name.to_string(), 3, 64),
             "name".to_string(),
+        ));
+    }
+    if let Some(description) = fields.description {
+        validator_fields.push(ValidateField(
+            ValidationType::StringLength(description.to_string(), 0, 500),
+            "description".to_string(),
+        ));
+    }
+    if let Some(website) = fields.website {
+        validator_fields.push(ValidateField(
+            ValidationType::StringLength(website.to_string(), 0, 200),
+            "website".to_string(),
+        ));
+    }
+    if let Some(tags) = fields.tags {
+        validator_fields.push(ValidateField(
+            ValidationType::Count(tags.len(), 0, 50),
+            "causes".to_string(),
+        ));
+    }
+
+    let mut errors = match Validator(validator_fields).validate() {
+        Ok(_) => vec![],
+        Err(ApiError::ValidationError(_errors)) => _errors,
+        Err(err) => return Err(err),
+    };
+
+    if let Some(date) = fields.date {
+        errors.append(&mut validate_date(date, fields.current_date, now));
+    }
+    if let Some(website) = fields.website {
+        errors.append(&mut validate_website(website));
+    }
+    if let Some(location) = fields.location {
+        errors.append(&mut validate_location(location));
+    }
+    if let Some(metadata) = fields.metadata {
+        errors.append(&mut validate_metadata(metadata));
+    }
+    if let Some(typed_metadata) = fields.typed_metadata {
+        errors.append(&mut validate_typed_metadata(typed_metadata, fields.schema));
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
+}
+
+// An end date of 0 means the event has no end date
+fn validate_date(
+    date: &DateRange,
+    current_date: Option<&DateRange>,
+    now: u64,
+) -> Vec<ValidationResponse> {
+    let mut errors = vec![];
+
+    let is_start_date_changed =
+        current_date.is_none_or(|_current_date| _current_date.start_date != date.start_date);
+    if is_start_date_changed && date.start_date < now.saturating_sub(MAX_EVENT_AGE) {
+        errors.push(validation_error(
+            "date.start_date",
+            "The start date can't be more than a year in the past",
+        ));
+    }
+
+    if date.end_date > 0 && date.end_date < date.start_date {
+        errors.push(validation_error(
+            "date.end_date",
+            "The end date can't be before the start date",
+        ));
+    }
+
+    errors
+}
+
+// The website is optional, when set it needs to be a http(s) url with a domain
+fn validate_website(website: &str) -> Vec<ValidationResponse> {
+    if website.is_empty() {
+        return vec![];
+    }
+
+    match is_valid_url(website) {
+        true => vec![],
+        false => vec![validation_error(
+            "website",
+            "The website must be a valid http or https url",
+        )],
+    }
+}
+
+// A valid url is a http or https url with a domain and without whitespace
+fn is_valid_url(url: &str) -> bool {
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .map(|rest| rest.split(['/', '?', '#']).next().unwrap_or(""));
+
+    match host {
+        Some(_host) => {
+            !url.chars().any(char::is_whitespace)
+                && _host.contains('.')
+                && !_host.starts_with('.')
+                && !_host.ends_with('.')
+        }
+        None => false,
+    }
+}
+
+fn validate_location(location: &Location) -> Vec<ValidationResponse> {
+    let physical = match location {
+        Location::Physical(_physical) => _physical,
+        Location::MultiLocation(_multi_location) => &_multi_location.physical,
+        _ => return vec![],
+    };
+
+    let mut errors = vec![];
+    if !(-90.0..=90.0).contains(&physical.lattitude) {
+        errors.push(validation_error(
+            "location.lattitude",
+            "The latitude must be between -90 and 90",
+        ));
+    }
+    if !(-180.0..=180.0).contains(&physical.longtitude) {
+        errors.push(validation_error(
+            "location.longtitude",
+            "The longitude must be between -180 and 180",
+        ));
+    }
+    errors
+}
+
+fn validate_metadata(metadata: &str) -> Vec<ValidationResponse> {
+    match metadata.len() > MAX_METADATA_BYTES {
+        true => vec![validation_error(
+            "metadata",
+            &format!(
+                "The metadata can't be larger than {} bytes",
+                MAX_METADATA_BYTES
+            ),
+        )],
+        false => vec![],
+    }
+}
+
+// Validate the typed metadata values, when the group has a schema the keys and types are checked against it
+fn validate_typed_metadata(
+    typed_metadata: &HashMap<String, MetadataValue>,
+    schema: Option<&MetadataSchema>,
+) -> Vec<ValidationResponse> {
+    let mut errors = vec![];
+
+    if typed_metadata.len() > MAX_TYPED_METADATA_KEYS {
+        errors.push(validation_error(
+            "typed_metadata",
+            &format!(
+                "There can't be more than {} metadata keys",
+                MAX_TYPED_METADATA_KEYS
+            ),
+        ));
+    }
+
+    // Sorted so the errors are returned in a stable order
+    let mut keys: Vec<&String> = typed_metadata.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let field = format!("typed_metadata.{}", key);
+        let value = &typed_metadata[key];
+
+        if key.is_empty() || key.len() > MAX_TYPED_METADATA_KEY_LENGTH {
+            errors.push(validation_error(
+                &field,
+                &format!(
+                    "The key must be between 1 and {} characters",
+                    MAX_TYPED_METADATA_KEY_LENGTH
+                ),
+            ));
+        }
+
+        match value {
+            MetadataValue::Text(_value) | MetadataValue::Url(_value)
+                if _value.len() > MAX_TYPED_METADATA_VALUE_LENGTH =>
+            {
+                errors.push(validation_error(
+                    &field,
+                    &format!(
+                        "The value can't be longer than {} characters",
+                        MAX_TYPED_METADATA_VALUE_LENGTH
+                    ),
+                ))
+            }
+            MetadataValue::Url(_value) if !is_valid_url(_value) => errors.push(validation_error(
+                &field,
+                "The value must be a valid http or https url",
+            )),
+            MetadataValue::Number(_value) if !_value.is_finite() => errors.push(validation_error(
+                &field,
+                "The value must be a finite number",
+            )),
+            _ => {}
+        }
+
+        if let Some(_schema) = schema {
+            match _schema.fields.iter().find(|_field| &_field.key == key) {
+                Some(_field) if _field.value_type != value.value_type() => {
+                    errors.push(validation_error(
+                        &field,
+                        &format!("The value must be of type {:?}", _field.value_type),
+                    ))
+                }
+                Some(_) => {}
+                None => errors.push(validation_error(
+                    &field,
+                    "The key is not part of the metadata schema of the group",
+                )),
+            }
+        }
+    }
+
+    if let Some(_schema) = schema {
+        _schema
+            .fields
+            .iter()
+            .filter(|_field| _field.required && !typed_metadata.contains_key(&_field.key))
+            .for_each(|_field| {
+                errors.push(validation_error(
+                    &format!("typed_metadata.{}", _field.key),
+                    "The key is required by the metadata schema of the group",
+                ))
+            });
+    }
+
+    errors
+}
+
+// Validate the fields of a metadata schema before it is registered
+pub fn validate_metadata_schema(schema: &MetadataSchema) -> Result<(), ApiError> {
+    let mut errors = vec![];
+
+    if schema.fields.len() > MAX_TYPED_METADATA_KEYS {
+        errors.push(validation_error(
+            "fields",
+            &format!(
+                "There can't be more than {} metadata keys",
+                MAX_TYPED_METADATA_KEYS
+            ),
+        ));
+    }
+
+    for (index, field) in schema.fields.iter().enumerate() {
+        if field.key.is_empty() || field.key.len() > MAX_TYPED_METADATA_KEY_LENGTH {
+            errors.push(validation_error(
+                &format!("fields.{}.key", index),
+                &format!(
+                    "The key must be between 1 and {} characters",
+                    MAX_TYPED_METADATA_KEY_LENGTH
+                ),
+            ));
+        }
+        if schema.fields[..index]
+            .iter()
+            .any(|_field| _field.key == field.key)
+        {
+            errors.push(validation_error(
+                &format!("fields.{}.key", index),
+                "The key is already part of the schema",
+            ));
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
+}
+
+// Validate a ticket tier, the supply is checked against the sold tickets by the store
+pub fn validate_ticket_tier(tier: &PostTicketTier) -> Result<(), ApiError> {
+    let mut errors = vec![];
+
+    if tier.name.is_empty() || tier.name.len() > 64 {
+        errors.push(validation_error(
+            "name",
+            "The name must be between 1 and 64 characters",
+        ));
+    }
+    if tier.supply == 0 {
+        errors.push(validation_error(
+            "supply",
+            "The supply must be at least 1 ticket",
+        ));
+    }
+    if tier.sale_end > 0 && tier.sale_end < tier.sale_start {
+        errors.push(validation_error(
+            "sale_end",
+            "The end of the sale can't be before the start of the sale",
+        ));
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
+}
+
+// Validate a session, the time slot needs to fall within the dates of the event
+pub fn validate_event_session(
+    session: &PostEventSession,
+    event_date: &DateRange,
+) -> Result<(), ApiError> {
+    let mut errors = match Validator(vec![
+        ValidateField(
+            ValidationType::StringLength(session.title.clone(), 1, 100),
+            "title".to_string(),
         ),
         ValidateField(
-            ValidationType::StringLength(post_event.description, 0, 500),
+            ValidationType::StringLength(session.description.clone(), 0, 500),
             "description".to_string(),
         ),
         ValidateField(
-            ValidationType::StringLength(post_event.website, 0, 200),
-            "website".to_string(),
+            ValidationType::Count(session.speakers.len(), 0, 20),
+            "speakers".to_string(),
         ),
         ValidateField(
-            ValidationType::Count(post_event.tags.len(), 0, 50),
-            "causes".to_string(),
+            ValidationType::Count(session.tags.len(), 0, 50),
+            "tags".to_string(),
+        ),
+    ])
+    .validate()
+    {
+        Ok(_) => vec![],
+        Err(ApiError::ValidationError(_errors)) => _errors,
+        Err(err) => return Err(err),
+    };
+
+    if session.date.end_date <= session.date.start_date {
+        errors.push(validation_error(
+            "date.end_date",
+            "The end of the session must be after the start of the session",
+        ));
+    }
+    if session.date.start_date < event_date.start_date {
+        errors.push(validation_error(
+            "date.start_date",
+            "The session can't start before the event",
+        ));
+    }
+    if event_date.end_date > 0 && session.date.end_date > event_date.end_date {
+        errors.push(validation_error(
+            "date.end_date",
+            "The session can't end after the event",
+        ));
+    }
+
+    match &session.location {
+        SessionLocation::Room(room) if room.is_empty() || room.len() > 100 => errors.push(
+            validation_error("location", "The room must be between 1 and 100 characters"),
         ),
-    ];
+        SessionLocation::Digital(link) if !is_valid_url(link) => errors.push(validation_error(
+            "location",
+            "The link must be a valid http or https url",
+        )),
+        _ => {}
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
+}
 
-    Validator(validator_fields).validate()
+// Validate that the stored sessions still fall within the dates of the event when the dates of the event change
+// the sessions that fall outside need to be moved or removed before the dates can be changed
+pub fn validate_event_sessions_date(
+    sessions: &[EventSession],
+    event_date: &DateRange,
+) -> Result<(), ApiError> {
+    let errors: Vec<ValidationResponse> = sessions
+        .iter()
+        .filter(|session| {
+            session.date.start_date < event_date.start_date
+                || (event_date.end_date > 0 && session.date.end_date > event_date.end_date)
+        })
+        .map(|session| {
+            validation_error(
+                &format!("sessions.{}", session.id),
+                "The session falls outside the new dates of the event",
+            )
+        })
+        .collect();
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
 }
 
-pub fn validate_update_event(update_event: UpdateEvent) -> Result<(), ApiError> {
-    let validator_fields = vec![
+// Validate a speaker or sponsor profile, the link is optional
+pub fn validate_event_profile(profile: &PostEventProfile) -> Result<(), ApiError> {
+    let mut errors = match Validator(vec![
         ValidateField(
-            ValidationType::StringLength(update_event.name, 3, 64),
+            ValidationType::StringLength(profile.name.clone(), 1, 100),
             "name".to_string(),
         ),
         ValidateField(
-            ValidationType::StringLength(update_event.description, 0, 500),
-            "description".to_string(),
+            ValidationType::StringLength(profile.bio.clone(), 0, 1_000),
+            "bio".to_string(),
         ),
-        ValidateField(
-            ValidationType::StringLength(update_event.website, 0, 200),
-            "website".to_string(),
-        ),
-        ValidateField(
-            ValidationType::Count(update_event.tags.len(), 0, 50),
-            "causes".to_string(),
-        ),
-    ];
+    ])
+    .validate()
+    {
+        Ok(_) => vec![],
+        Err(ApiError::ValidationError(_errors)) => _errors,
+        Err(err) => return Err(err),
+    };
+
+    if !profile.link.is_empty() && !is_valid_url(&profile.link) {
+        errors.push(validation_error(
+            "link",
+            "The link must be a valid http or https url",
+        ));
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ApiError::ValidationError(errors)),
+    }
+}
+
+fn validation_error(field: &str, message: &str) -> ValidationResponse {
+    ValidationResponse {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_scalable_canister::ic_scalable_misc::enums::asset_type::Asset;
+    use shared::event_models::{MetadataField, MetadataValueType, TicketPrice};
+
+    static DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+    static NOW: u64 = 20_000 * DAY;
+
+    fn validate(
+        date: DateRange,
+        website: &str,
+        location: Location,
+        metadata: Option<String>,
+    ) -> Vec<String> {
+        let fields = EventFields {
+            name: Some("Conference"),
+            description: Some(""),
+            website: Some(website),
+            tags: Some(&[]),
+            date: Some(&date),
+            location: Some(&location),
+            metadata: metadata.as_ref(),
+            typed_metadata: None,
+            schema: None,
+            current_date: None,
+        };
+
+        match validate_event_fields(fields, NOW) {
+            Ok(_) => vec![],
+            Err(ApiError::ValidationError(errors)) => {
+                errors.into_iter().map(|error| error.field).collect()
+            }
+            Err(err) => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    fn date(start_date: u64, end_date: u64) -> DateRange {
+        DateRange {
+            start_date,
+            end_date,
+        }
+    }
+
+    #[test]
+    fn accepts_valid_event() {
+        let errors = validate(
+            date(NOW + DAY, NOW + 2 * DAY),
+            "https://catalyze.one/events?id=1",
+            Location::default(),
+            Some("{}".to_string()),
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn accepts_event_without_end_date() {
+        let errors = validate(date(NOW, 0), "", Location::default(), None);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn rejects_end_date_before_start_date() {
+        let errors = validate(
+            date(NOW + 2 * DAY, NOW + DAY),
+            "",
+            Location::default(),
+            None,
+        );
+        assert_eq!(errors, vec!["date.end_date"]);
+    }
+
+    #[test]
+    fn rejects_start_date_in_distant_past() {
+        let errors = validate(date(NOW - 400 * DAY, 0), "", Location::default(), None);
+        assert_eq!(errors, vec!["date.start_date"]);
+    }
+
+    #[test]
+    fn validates_start_date_age_only_when_changed() {
+        let old_date = date(NOW - 400 * DAY, NOW - 399 * DAY);
+        let changed = |start_date: u64, end_date: u64| {
+            let new_date = date(start_date, end_date);
+            validate_date(&new_date, Some(&old_date), NOW)
+                .into_iter()
+                .map(|error| error.field)
+                .collect::<Vec<String>>()
+        };
+
+        assert!(changed(NOW - 400 * DAY, NOW - 398 * DAY).is_empty());
+        assert_eq!(changed(NOW - 401 * DAY, 0), vec!["date.start_date"]);
+        assert!(changed(NOW + DAY, 0).is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_website() {
+        for website in [
+            "catalyze.one",
+            "https://",
+            "https://catalyze",
+            "http://cat alyze.one",
+            "ftp://catalyze.one",
+        ] {
+            let errors = validate(date(NOW, 0), website, Location::default(), None);
+            assert_eq!(errors, vec!["website"], "{}", website);
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_coordinates() {
+        let physical = |lattitude: f32, longtitude: f32| {
+            let mut location = match Location::Physical(Default::default()) {
+                Location::Physical(physical) => physical,
+                _ => unreachable!(),
+            };
+            location.lattitude = lattitude;
+            location.longtitude = longtitude;
+            Location::Physical(location)
+        };
+
+        assert!(validate(date(NOW, 0), "", physical(52.0, 4.9), None).is_empty());
+        assert_eq!(
+            validate(date(NOW, 0), "", physical(91.0, 4.9), None),
+            vec!["location.lattitude"]
+        );
+        assert_eq!(
+            validate(date(NOW, 0), "", physical(52.0, -181.0), None),
+            vec!["location.longtitude"]
+        );
+    }
+
+    #[test]
+    fn validates_only_supplied_fields() {
+        let fields = EventFields {
+            website: Some("catalyze.one"),
+            ..Default::default()
+        };
+
+        match validate_event_fields(fields, NOW) {
+            Err(ApiError::ValidationError(errors)) => {
+                let fields: Vec<String> = errors.into_iter().map(|error| error.field).collect();
+                assert_eq!(fields, vec!["website"]);
+            }
+            _ => panic!("expected a validation error"),
+        }
+        assert!(validate_event_fields(EventFields::default(), NOW).is_ok());
+    }
+
+    #[test]
+    fn rejects_oversized_metadata() {
+        let metadata = Some("a".repeat(MAX_METADATA_BYTES + 1));
+        let errors = validate(date(NOW, 0), "", Location::default(), metadata);
+        assert_eq!(errors, vec!["metadata"]);
+    }
+
+    fn validate_typed(
+        typed_metadata: Vec<(&str, MetadataValue)>,
+        schema: Option<&MetadataSchema>,
+    ) -> Vec<String> {
+        let typed_metadata: HashMap<String, MetadataValue> = typed_metadata
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+
+        validate_typed_metadata(&typed_metadata, schema)
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    fn schema() -> MetadataSchema {
+        MetadataSchema {
+            group_identifier: candid::Principal::anonymous(),
+            fields: vec![
+                MetadataField {
+                    key: "capacity".to_string(),
+                    value_type: MetadataValueType::Number,
+                    required: true,
+                },
+                MetadataField {
+                    key: "livestream".to_string(),
+                    value_type: MetadataValueType::Url,
+                    required: false,
+                },
+            ],
+            updated_by: candid::Principal::anonymous(),
+            updated_on: NOW,
+        }
+    }
+
+    #[test]
+    fn validates_typed_metadata_values() {
+        let errors = validate_typed(
+            vec![
+                ("venue", MetadataValue::Text("Main hall".to_string())),
+                ("livestream", MetadataValue::Url("catalyze.one".to_string())),
+                ("capacity", MetadataValue::Number(f64::NAN)),
+                ("", MetadataValue::Bool(true)),
+            ],
+            None,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "typed_metadata.",
+                "typed_metadata.capacity",
+                "typed_metadata.livestream"
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_typed_metadata_against_schema() {
+        let schema = schema();
+
+        let valid = validate_typed(
+            vec![
+                ("capacity", MetadataValue::Number(120.0)),
+                (
+                    "livestream",
+                    MetadataValue::Url("https://catalyze.one/live".to_string()),
+                ),
+            ],
+            Some(&schema),
+        );
+        assert!(valid.is_empty());
+
+        let errors = validate_typed(
+            vec![
+                ("livestream", MetadataValue::Text("soon".to_string())),
+                ("venue", MetadataValue::Text("Main hall".to_string())),
+            ],
+            Some(&schema),
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "typed_metadata.livestream",
+                "typed_metadata.venue",
+                "typed_metadata.capacity"
+            ]
+        );
+    }
+
+    fn session(id: u32, start_date: u64, end_date: u64) -> EventSession {
+        EventSession {
+            id,
+            title: "Keynote".to_string(),
+            description: "".to_string(),
+            speakers: vec![],
+            date: date(start_date, end_date),
+            location: SessionLocation::Room("Main hall".to_string()),
+            tags: vec![],
+            updated_on: NOW,
+            created_on: NOW,
+        }
+    }
+
+    #[test]
+    fn rejects_event_dates_that_leave_sessions_outside() {
+        let sessions = vec![
+            session(0, NOW + DAY, NOW + DAY + 1),
+            session(1, NOW + 3 * DAY, NOW + 3 * DAY + 1),
+        ];
+        let invalid =
+            |event_date: DateRange| match validate_event_sessions_date(&sessions, &event_date) {
+                Ok(_) => vec![],
+                Err(ApiError::ValidationError(errors)) => {
+                    errors.into_iter().map(|error| error.field).collect()
+                }
+                Err(err) => panic!("unexpected error {:?}", err),
+            };
+
+        assert!(invalid(date(NOW, NOW + 4 * DAY)).is_empty());
+        assert!(invalid(date(NOW, 0)).is_empty());
+        assert_eq!(invalid(date(NOW, NOW + 2 * DAY)), vec!["sessions.1"]);
+        assert_eq!(invalid(date(NOW + 2 * DAY, 0)), vec!["sessions.0"]);
+    }
+
+    fn errors(result: Result<(), ApiError>) -> Vec<String> {
+        match result {
+            Ok(_) => vec![],
+            Err(ApiError::ValidationError(errors)) => {
+                errors.into_iter().map(|error| error.field).collect()
+            }
+            Err(err) => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    // Validate a valid fixture after applying the change, returns the fields of the validation errors
+    fn invalid_fields<T>(
+        mut fixture: T,
+        change: impl FnOnce(&mut T),
+        validate: impl Fn(&T) -> Result<(), ApiError>,
+    ) -> Vec<String> {
+        change(&mut fixture);
+        errors(validate(&fixture))
+    }
+
+    fn ticket_tier() -> PostTicketTier {
+        PostTicketTier {
+            name: "Regular".to_string(),
+            price: TicketPrice {
+                ledger: candid::Principal::anonymous(),
+                amount: candid::Nat::from(0_u64),
+            },
+            supply: 100,
+            sale_start: 0,
+            sale_end: 0,
+        }
+    }
+
+    fn invalid_ticket_tier(change: impl FnOnce(&mut PostTicketTier)) -> Vec<String> {
+        invalid_fields(ticket_tier(), change, validate_ticket_tier)
+    }
+
+    #[test]
+    fn validates_ticket_tier() {
+        assert!(invalid_ticket_tier(|_| {}).is_empty());
+        assert_eq!(invalid_ticket_tier(|tier| tier.name.clear()), vec!["name"]);
+        assert_eq!(
+            invalid_ticket_tier(|tier| tier.name = "a".repeat(65)),
+            vec!["name"]
+        );
+        assert_eq!(invalid_ticket_tier(|tier| tier.supply = 0), vec!["supply"]);
+    }
+
+    #[test]
+    fn validates_ticket_sale_window() {
+        // A sale end of 0 means the sale has no end
+        assert!(invalid_ticket_tier(|tier| tier.sale_start = NOW).is_empty());
+        assert!(invalid_ticket_tier(|tier| {
+            tier.sale_start = NOW;
+            tier.sale_end = NOW + DAY;
+        })
+        .is_empty());
+        assert_eq!(
+            invalid_ticket_tier(|tier| {
+                tier.sale_start = NOW + DAY;
+                tier.sale_end = NOW;
+            }),
+            vec!["sale_end"]
+        );
+    }
+
+    fn post_session() -> PostEventSession {
+        PostEventSession {
+            title: "Keynote".to_string(),
+            description: "".to_string(),
+            speakers: vec![],
+            date: date(NOW, NOW + DAY),
+            location: SessionLocation::Room("Main hall".to_string()),
+            tags: vec![],
+        }
+    }
+
+    fn invalid_session(
+        event_date: DateRange,
+        change: impl FnOnce(&mut PostEventSession),
+    ) -> Vec<String> {
+        invalid_fields(post_session(), change, |session| {
+            validate_event_session(session, &event_date)
+        })
+    }
+
+    #[test]
+    fn validates_session() {
+        let invalid =
+            |change: fn(&mut PostEventSession)| invalid_session(date(NOW, NOW + 2 * DAY), change);
+
+        assert!(invalid(|_| {}).is_empty());
+        assert_eq!(invalid(|session| session.title.clear()), vec!["title"]);
+        assert_eq!(
+            invalid(|session| session.location = SessionLocation::Room("".to_string())),
+            vec!["location"]
+        );
+        assert_eq!(
+            invalid(|session| {
+                session.location = SessionLocation::Digital("catalyze.one".to_string())
+            }),
+            vec!["location"]
+        );
+    }
+
+    #[test]
+    fn validates_session_within_event_dates() {
+        let session_date = |start_date: u64, end_date: u64, event_date: DateRange| {
+            invalid_session(event_date, |session| {
+                session.date = date(start_date, end_date)
+            })
+        };
+
+        assert_eq!(
+            session_date(NOW + DAY, NOW + DAY, date(NOW, NOW + 2 * DAY)),
+            vec!["date.end_date"]
+        );
+        assert_eq!(
+            session_date(NOW - 1, NOW + DAY, date(NOW, NOW + 2 * DAY)),
+            vec!["date.start_date"]
+        );
+        assert_eq!(
+            session_date(NOW + DAY, NOW + 3 * DAY, date(NOW, NOW + 2 * DAY)),
+            vec!["date.end_date"]
+        );
+        // An event without an end date doesn't limit the end of its sessions
+        assert!(session_date(NOW + DAY, NOW + 3 * DAY, date(NOW, 0)).is_empty());
+    }
+
+    fn profile() -> PostEventProfile {
+        PostEventProfile {
+            name: "Ada".to_string(),
+            principal: None,
+            bio: "".to_string(),
+            image: Asset::None,
+            link: "".to_string(),
+        }
+    }
+
+    fn invalid_profile(change: impl FnOnce(&mut PostEventProfile)) -> Vec<String> {
+        invalid_fields(profile(), change, validate_event_profile)
+    }
 
-    Validator(validator_fields).validate()
+    #[test]
+    fn validates_profile() {
+        // The link is optional
+        assert!(invalid_profile(|_| {}).is_empty());
+        assert!(invalid_profile(|profile| {
+            profile.bio = "Speaker".to_string();
+            profile.link = "https://catalyze.one/ada".to_string();
+        })
+        .is_empty());
+        assert_eq!(
+            invalid_profile(|profile| profile.name.clear()),
+            vec!["name"]
+        );
+        assert_eq!(
+            invalid_profile(|profile| profile.bio = "a".repeat(1_001)),
+            vec!["bio"]
+        );
+        assert_eq!(
+            invalid_profile(|profile| profile.link = "catalyze.one".to_string()),
+            vec!["link"]
+        );
+    }
 }
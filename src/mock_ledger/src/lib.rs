@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+
+use candid::{Nat, Principal};
+
+use shared::icrc_models::{
+    Account, Allowance, AllowanceArgs, ApproveArgs, ApproveError, TransferArg, TransferError,
+    TransferFromArgs, TransferFromError,
+};
+
+// In memory ICRC-1 / ICRC-2 ledger, balances and allowances are lost on upgrade
+// transfers with a created_at_time are deduplicated, expiration of allowances and the transaction window are not supported
+#[derive(Default)]
+pub struct MockLedger {
+    pub fee: Nat,
+    balances: HashMap<Account, Nat>,
+    // (owner, spender) => allowance
+    allowances: HashMap<(Account, Account), Nat>,
+    // The block indexes of the deduplicated transfers
+    transactions: HashMap<String, Nat>,
+    blocks: u64,
+}
+
+impl MockLedger {
+    pub fn new(fee: Nat) -> Self {
+        Self {
+            fee,
+            ..Default::default()
+        }
+    }
+
+    // Test helper to add tokens to an account without a sender
+    pub fn mint(&mut self, to: Account, amount: Nat) -> Nat {
+        let to = normalize(to);
+        let balance = self.balance_of(&to);
+        self.balances.insert(to, balance + amount);
+        self.next_block()
+    }
+
+    pub fn balance_of(&self, account: &Account) -> Nat {
+        self.balances
+            .get(&normalize(account.clone()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn allowance(&self, args: &AllowanceArgs) -> Allowance {
+        Allowance {
+            allowance: self
+                .allowances
+                .get(&(
+                    normalize(args.account.clone()),
+                    normalize(args.spender.clone()),
+                ))
+                .cloned()
+                .unwrap_or_default(),
+            expires_at: None,
+        }
+    }
+
+    pub fn transfer(&mut self, caller: Principal, arg: TransferArg) -> Result<Nat, TransferError> {
+        let transaction = arg
+            .created_at_time
+            .map(|_| format!("transfer {} {:?}", caller, arg));
+        if let Some(duplicate_of) = transaction
+            .as_ref()
+            .and_then(|transaction| self.transactions.get(transaction))
+        {
+            return Err(TransferError::Duplicate {
+                duplicate_of: duplicate_of.clone(),
+            });
+        }
+
+        if let Some(fee) = arg.fee {
+            if fee != self.fee {
+                return Err(TransferError::BadFee {
+                    expected_fee: self.fee.clone(),
+                });
+            }
+        }
+
+        let from = normalize(Account {
+            owner: caller,
+            subaccount: arg.from_subaccount,
+        });
+        let balance = self.balance_of(&from);
+        let total = arg.amount.clone() + self.fee.clone();
+        if balance < total {
+            return Err(TransferError::InsufficientFunds { balance });
+        }
+
+        self.move_tokens(from, balance - total, normalize(arg.to), arg.amount);
+        Ok(self.next_transaction_block(transaction))
+    }
+
+    pub fn approve(&mut self, caller: Principal, args: ApproveArgs) -> Result<Nat, ApproveError> {
+        let from = normalize(Account {
+            owner: caller,
+            subaccount: args.from_subaccount,
+        });
+        let balance = self.balance_of(&from);
+        if balance < self.fee {
+            return Err(ApproveError::InsufficientFunds { balance });
+        }
+
+        let spender = normalize(args.spender);
+        let current_allowance = self
+            .allowances
+            .get(&(from.clone(), spender.clone()))
+            .cloned()
+            .unwrap_or_default();
+        if let Some(expected_allowance) = args.expected_allowance {
+            if expected_allowance != current_allowance {
+                return Err(ApproveError::AllowanceChanged { current_allowance });
+            }
+        }
+
+        // The approval fee is charged to the approver
+        self.balances
+            .insert(from.clone(), balance - self.fee.clone());
+        self.allowances.insert((from, spender), args.amount);
+        Ok(self.next_block())
+    }
+
+    pub fn transfer_from(
+        &mut self,
+        caller: Principal,
+        args: TransferFromArgs,
+    ) -> Result<Nat, TransferFromError> {
+        let transaction = args
+            .created_at_time
+            .map(|_| format!("transfer_from {} {:?}", caller, args));
+        if let Some(duplicate_of) = transaction
+            .as_ref()
+            .and_then(|transaction| self.transactions.get(transaction))
+        {
+            return Err(TransferFromError::Duplicate {
+                duplicate_of: duplicate_of.clone(),
+            });
+        }
+
+        let from = normalize(args.from);
+        let spender = normalize(Account {
+            owner: caller,
+            subaccount: args.spender_subaccount,
+        });
+        let total = args.amount.clone() + self.fee.clone();
+
+        let allowance = self
+            .allowances
+            .get(&(from.clone(), spender.clone()))
+            .cloned()
+            .unwrap_or_default();
+        if allowance < total {
+            return Err(TransferFromError::InsufficientAllowance { allowance });
+        }
+
+        let balance = self.balance_of(&from);
+        if balance < total {
+            return Err(TransferFromError::InsufficientFunds { balance });
+        }
+
+        self.allowances
+            .insert((from.clone(), spender), allowance - total.clone());
+        self.move_tokens(from, balance - total, normalize(args.to), args.amount);
+        Ok(self.next_transaction_block(transaction))
+    }
+
+    fn move_tokens(&mut self, from: Account, from_balance: Nat, to: Account, amount: Nat) {
+        self.balances.insert(from, from_balance);
+        let to_balance = self.balance_of(&to);
+        self.balances.insert(to, to_balance + amount);
+    }
+
+    fn next_block(&mut self) -> Nat {
+        self.blocks += 1;
+        Nat::from(self.blocks)
+    }
+
+    fn next_transaction_block(&mut self, transaction: Option<String>) -> Nat {
+        let block_index = self.next_block();
+        if let Some(_transaction) = transaction {
+            self.transactions.insert(_transaction, block_index.clone());
+        }
+        block_index
+    }
+}
+
+// The default subaccount (32 zero bytes) is the same account as no subaccount
+fn normalize(account: Account) -> Account {
+    match account.subaccount {
+        Some(subaccount) if subaccount.iter().all(|byte| *byte == 0) => Account {
+            owner: account.owner,
+            subaccount: None,
+        },
+        _ => account,
+    }
+}
+
+// The canister methods are only exported in the wasm build, so the mock ledger can be used as a library in the tests of the child canister
+#[cfg(target_arch = "wasm32")]
+mod canister {
+    use std::cell::RefCell;
+
+    use candid::Nat;
+    use ic_cdk::{caller, init, query, update};
+
+    use shared::icrc_models::{
+        Account, Allowance, AllowanceArgs, ApproveArgs, ApproveError, TransferArg, TransferError,
+        TransferFromArgs, TransferFromError,
+    };
+
+    use super::MockLedger;
+
+    // The fee that is used when no fee is passed on install
+    static DEFAULT_FEE: u64 = 10_000;
+
+    thread_local! {
+        pub static LEDGER: RefCell<MockLedger> = RefCell::new(MockLedger::new(Nat::from(DEFAULT_FEE)));
+    }
+
+    #[init]
+    fn init(fee: Option<Nat>) {
+        if let Some(_fee) = fee {
+            LEDGER.with(|ledger| ledger.borrow_mut().fee = _fee);
+        }
+    }
+
+    #[query]
+    fn icrc1_fee() -> Nat {
+        LEDGER.with(|ledger| ledger.borrow().fee.clone())
+    }
+
+    #[query]
+    fn icrc1_balance_of(account: Account) -> Nat {
+        LEDGER.with(|ledger| ledger.borrow().balance_of(&account))
+    }
+
+    #[update]
+    fn icrc1_transfer(arg: TransferArg) -> Result<Nat, TransferError> {
+        LEDGER.with(|ledger| ledger.borrow_mut().transfer(caller(), arg))
+    }
+
+    #[update]
+    fn icrc2_approve(args: ApproveArgs) -> Result<Nat, ApproveError> {
+        LEDGER.with(|ledger| ledger.borrow_mut().approve(caller(), args))
+    }
+
+    #[query]
+    fn icrc2_allowance(args: AllowanceArgs) -> Allowance {
+        LEDGER.with(|ledger| ledger.borrow().allowance(&args))
+    }
+
+    #[update]
+    fn icrc2_transfer_from(args: TransferFromArgs) -> Result<Nat, TransferFromError> {
+        LEDGER.with(|ledger| ledger.borrow_mut().transfer_from(caller(), args))
+    }
+
+    // Test helper to add tokens to an account
+    #[update]
+    fn mint(to: Account, amount: Nat) -> Nat {
+        LEDGER.with(|ledger| ledger.borrow_mut().mint(to, amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::icrc_models::{event_subaccount, ticket_payment_subaccount};
+
+    fn principal(byte: u8) -> Principal {
+        Principal::from_slice(&[byte; 29])
+    }
+
+    fn account(owner: Principal, subaccount: Option<Vec<u8>>) -> Account {
+        Account { owner, subaccount }
+    }
+
+    fn transfer_arg(from_subaccount: Option<Vec<u8>>, to: Account, amount: u64) -> TransferArg {
+        TransferArg {
+            from_subaccount,
+            to,
+            fee: None,
+            created_at_time: None,
+            memo: None,
+            amount: Nat::from(amount),
+        }
+    }
+
+    fn ledger() -> MockLedger {
+        MockLedger::new(Nat::from(10_u64))
+    }
+
+    #[test]
+    fn transfer_charges_the_fee() {
+        let mut ledger = ledger();
+        let (alice, bob) = (principal(1), principal(2));
+        ledger.mint(account(alice, None), Nat::from(1_000_u64));
+
+        assert!(ledger
+            .transfer(alice, transfer_arg(None, account(bob, None), 500))
+            .is_ok());
+        assert_eq!(ledger.balance_of(&account(alice, None)), Nat::from(490_u64));
+        assert_eq!(ledger.balance_of(&account(bob, None)), Nat::from(500_u64));
+
+        assert!(matches!(
+            ledger.transfer(alice, transfer_arg(None, account(bob, None), 490)),
+            Err(TransferError::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn transfer_with_created_at_time_is_deduplicated() {
+        let mut ledger = ledger();
+        let (alice, bob) = (principal(1), principal(2));
+        ledger.mint(account(alice, None), Nat::from(1_000_u64));
+
+        let mut arg = transfer_arg(None, account(bob, None), 100);
+        arg.created_at_time = Some(1);
+        let block_index = ledger.transfer(alice, arg.clone()).unwrap();
+
+        assert!(matches!(
+            ledger.transfer(alice, arg.clone()),
+            Err(TransferError::Duplicate { duplicate_of }) if duplicate_of == block_index
+        ));
+        assert_eq!(ledger.balance_of(&account(bob, None)), Nat::from(100_u64));
+
+        // A different memo is a different transfer
+        arg.memo = Some(vec![1]);
+        assert!(ledger.transfer(alice, arg).is_ok());
+        assert_eq!(ledger.balance_of(&account(bob, None)), Nat::from(200_u64));
+    }
+
+    #[test]
+    fn default_subaccount_is_the_same_account() {
+        let mut ledger = ledger();
+        let alice = principal(1);
+        ledger.mint(account(alice, Some(vec![0; 32])), Nat::from(100_u64));
+
+        assert_eq!(ledger.balance_of(&account(alice, None)), Nat::from(100_u64));
+    }
+
+    #[test]
+    fn transfer_from_needs_an_allowance_for_the_amount_and_fee() {
+        let mut ledger = ledger();
+        let (buyer, child) = (principal(1), principal(2));
+        let event_account = account(child, Some(event_subaccount(&principal(3))));
+        ledger.mint(account(buyer, None), Nat::from(1_000_u64));
+
+        let approve = |amount: u64| ApproveArgs {
+            from_subaccount: None,
+            spender: account(child, None),
+            amount: Nat::from(amount),
+            expected_allowance: None,
+            expires_at: None,
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        };
+        let transfer_from = TransferFromArgs {
+            spender_subaccount: None,
+            from: account(buyer, None),
+            to: event_account.clone(),
+            amount: Nat::from(500_u64),
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        };
+
+        assert!(ledger.approve(buyer, approve(500)).is_ok());
+        assert!(matches!(
+            ledger.transfer_from(child, transfer_from.clone()),
+            Err(TransferFromError::InsufficientAllowance { .. })
+        ));
+
+        assert!(ledger.approve(buyer, approve(510)).is_ok());
+        assert!(ledger.transfer_from(child, transfer_from.clone()).is_ok());
+        assert_eq!(ledger.balance_of(&event_account), Nat::from(500_u64));
+        // 2 approval fees and the transfer fee
+        assert_eq!(ledger.balance_of(&account(buyer, None)), Nat::from(470_u64));
+
+        // The allowance is used
+        assert!(ledger.transfer_from(child, transfer_from).is_err());
+    }
+
+    #[test]
+    fn ticket_payment_is_collected_and_refunded() {
+        let mut ledger = ledger();
+        let (buyer, child, event) = (principal(1), principal(2), principal(3));
+        let payment_subaccount = ticket_payment_subaccount(&event, &buyer);
+        let event_account = account(child, Some(event_subaccount(&event)));
+        ledger.mint(account(buyer, None), Nat::from(1_000_u64));
+
+        // The buyer pays the price plus the fee to the payment account
+        assert!(ledger
+            .transfer(
+                buyer,
+                transfer_arg(None, account(child, Some(payment_subaccount.clone())), 510),
+            )
+            .is_ok());
+
+        // The child moves the price to the event account
+        assert!(ledger
+            .transfer(
+                child,
+                transfer_arg(Some(payment_subaccount.clone()), event_account.clone(), 500),
+            )
+            .is_ok());
+        assert_eq!(
+            ledger.balance_of(&account(child, Some(payment_subaccount))),
+            Nat::from(0_u64)
+        );
+        assert_eq!(ledger.balance_of(&event_account), Nat::from(500_u64));
+
+        // The child refunds the price minus the fee when the event is canceled
+        assert!(ledger
+            .transfer(
+                child,
+                transfer_arg(event_account.subaccount.clone(), account(buyer, None), 490),
+            )
+            .is_ok());
+        assert_eq!(ledger.balance_of(&event_account), Nat::from(0_u64));
+        assert_eq!(ledger.balance_of(&account(buyer, None)), Nat::from(970_u64));
+    }
+
+    #[test]
+    fn payment_subaccounts_differ_per_event_and_payer() {
+        let (buyer, other_buyer, event) = (principal(1), principal(2), principal(3));
+
+        assert_eq!(ticket_payment_subaccount(&event, &buyer).len(), 32);
+        assert_ne!(
+            ticket_payment_subaccount(&event, &buyer),
+            ticket_payment_subaccount(&event, &other_buyer)
+        );
+        assert_ne!(
+            ticket_payment_subaccount(&event, &buyer),
+            ticket_payment_subaccount(&buyer, &event)
+        );
+        assert_ne!(event_subaccount(&event), event_subaccount(&buyer));
+    }
+}
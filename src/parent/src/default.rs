@@ -43,8 +43,12 @@ pub fn post_upgrade() {
             });
 
             // Use a timer to trigger the upgrade_children method to upgrade the child WASMs
+            // and backfill the groups of the child canisters once they are upgraded
             set_timer(Duration::from_secs(0), || {
-                ic_cdk::spawn(ScalableData::upgrade_children());
+                ic_cdk::spawn(async {
+                    ScalableData::upgrade_children().await;
+                    backfill_group_canisters().await;
+                });
             });
         }
         // If the child wasm data is not found, continue restoring the old store
@@ -59,10 +63,27 @@ pub fn post_upgrade() {
                 source: "post_upgrade".to_string(),
                 data: format!("Error: {}", err),
             });
+
+            // Use a timer to backfill the groups of the child canisters
+            set_timer(Duration::from_secs(0), || {
+                ic_cdk::spawn(backfill_group_canisters());
+            });
         }
     }
 }
 
+// Rebuild the group to canister mapping, the child canisters that failed are retried on the next upgrade or sync
+async fn backfill_group_canisters() {
+    if let Err(err) = ScalableData::sync_group_canisters().await {
+        add_log(PostLog {
+            log_type: LogType::Error,
+            description: "Groups of the child canisters not synced".to_string(),
+            source: "post_upgrade".to_string(),
+            data: format!("{:?}", err),
+        });
+    }
+}
+
 // Init methods thats get triggered when the canister is installed
 #[init]
 fn init() {
@@ -83,14 +104,14 @@ fn init() {
 // Hacky way to expose the candid interface to the outside world
 #[query(name = "__get_candid_interface_tmp_hack")]
 pub fn __export_did_tmp_() -> String {
+    use crate::migration::*;
     use candid::{export_service, Principal};
     use ic_cdk::api::management_canister::http_request::HttpResponse;
     use ic_scalable_canister::ic_scalable_misc::enums::api_error_type::ApiError;
-    use ic_scalable_canister::ic_scalable_misc::enums::filter_type::FilterType;
     use ic_scalable_canister::ic_scalable_misc::enums::wasm_version_type::WasmVersion;
     use ic_scalable_canister::ic_scalable_misc::models::canister_models::ScalableCanisterDetails;
     use ic_scalable_canister::ic_scalable_misc::models::http_models::HttpRequest;
-    use ic_scalable_canister::ic_scalable_misc::models::paged_response_models::PagedResponse;
+    use shared::event_facets::*;
     use shared::event_models::*;
     export_service!();
     __export_service()
@@ -1,4 +1,5 @@
 pub mod default;
 pub mod methods;
+pub mod migration;
 pub mod scalable_methods;
 pub mod store;
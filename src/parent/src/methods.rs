@@ -1,23 +1,115 @@
-use ic_cdk::query;
-use ic_scalable_canister::ic_scalable_misc::{
-    enums::filter_type::FilterType, models::paged_response_models::PagedResponse,
-};
+use candid::Principal;
+use ic_cdk::{api::is_controller as _is_controller, caller, query, update};
+use ic_scalable_canister::ic_scalable_misc::enums::api_error_type::ApiError;
 
-use shared::event_models::{EventFilter, EventResponse, EventSort};
+use shared::event_facets::EventFacetRequest;
+use shared::event_models::{
+    EventFilterExpression, EventResponse, EventSort, EventsCount, MetadataSchema,
+    PagedEventsResponse,
+};
 
-use super::store::ScalableData;
+use super::{
+    migration::{EventMigration, EventMigrationSelection},
+    store::ScalableData,
+};
 
 // Method used to get all the events from the child canisters filtered, sorted and paged
+// if a group is passed, only the child canisters that store events of the group are queried
+// when facets are requested they are counted over all the events that matched the filter
 // requires composite queries to be released to mainnet
-// TODO: Add group identifier
 // #[query(composite = true)]
 #[query]
 async fn get_events(
     limit: usize,
     page: usize,
-    filters: Vec<EventFilter>,
-    filter_type: FilterType,
-    sort: EventSort,
-) -> PagedResponse<EventResponse> {
-    ScalableData::get_child_canister_data(limit, page, filters, filter_type, sort).await
+    filter: EventFilterExpression,
+    sort: Vec<EventSort>,
+    group_identifier: Option<Principal>,
+    facets: Option<EventFacetRequest>,
+) -> Result<PagedEventsResponse, ApiError> {
+    ScalableData::get_child_canister_data(limit, page, filter, sort, group_identifier, facets).await
+}
+
+// Method used to get the amount of events for a list of groups summed over all the child canisters
+// optionally split into upcoming, past and cancelled events
+// requires composite queries to be released to mainnet
+#[query(composite = true)]
+async fn get_events_count(
+    group_identifiers: Vec<Principal>,
+    with_breakdown: bool,
+) -> Result<Vec<(Principal, EventsCount)>, ApiError> {
+    ScalableData::get_events_count(group_identifiers, with_breakdown).await
+}
+
+// Method used to get the amount of live (not deleted or cancelled) events per tag for a group summed over the child canisters
+// requires composite queries to be released to mainnet
+#[query(composite = true)]
+async fn get_tag_counts(group_identifier: Principal) -> Result<Vec<(u32, usize)>, ApiError> {
+    ScalableData::get_tag_counts(group_identifier).await
+}
+
+// Method used to get the metadata schema of a group
+#[query]
+fn get_metadata_schema(group_identifier: Principal) -> Option<MetadataSchema> {
+    ScalableData::get_metadata_schema(group_identifier)
+}
+
+// Method used to get the event attendee canister the child canisters record the ticket holders on
+#[query]
+fn get_event_attendee_canister() -> Option<Principal> {
+    ScalableData::get_event_attendee_canister()
+}
+
+// Method used to configure the event attendee canister, it is pushed to all the child canisters
+// the canisters that failed are returned in the error, setting it can be retried
+#[update(guard = "is_controller")]
+async fn set_event_attendee_canister(event_attendee_canister: Principal) -> Result<(), ApiError> {
+    ScalableData::set_event_attendee_canister(event_attendee_canister).await
+}
+
+// Method used to resolve the child canister that stores the event based on the identifier
+#[query]
+fn resolve_event_canister(identifier: Principal) -> Result<Principal, ApiError> {
+    ScalableData::resolve_event_canister(identifier)
+}
+
+// Method used to get events by their identifiers, the events can be spread over multiple child canisters
+// requires composite queries to be released to mainnet
+#[query(composite = true)]
+async fn get_events_by_identifiers(
+    identifiers: Vec<Principal>,
+) -> Vec<(Principal, Result<EventResponse, ApiError>)> {
+    ScalableData::get_events_by_identifiers(identifiers).await
+}
+
+// Method used to move events from one child canister to another, the identifiers of the events are preserved
+// the events are copied, verified on the target canister and then removed from the source canister
+// events that hold ticket payments stay on the source canister and are listed as skipped on the migration
+// the check-ins are moved with the events, the check-in codes stay valid because the child canisters share the check-in secret
+#[update(guard = "is_controller")]
+async fn migrate_events(
+    from: Principal,
+    to: Principal,
+    selection: EventMigrationSelection,
+) -> Result<EventMigration, ApiError> {
+    ScalableData::migrate_events(from, to, selection).await
+}
+
+// Method used to resume a migration that failed or got interrupted
+#[update(guard = "is_controller")]
+async fn resume_migration(id: u64) -> Result<EventMigration, ApiError> {
+    ScalableData::resume_migration(id).await
+}
+
+// Method used to get all the migrations and their progress
+#[query]
+fn get_migrations() -> Vec<EventMigration> {
+    ScalableData::get_migrations()
+}
+
+pub fn is_controller() -> Result<(), String> {
+    match _is_controller(&caller()) {
+        true => Ok(()),
+        false => Err("Unauthorized".to_string()),
+    }
 }
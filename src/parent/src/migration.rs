@@ -0,0 +1,519 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::{call, time};
+use ic_scalable_canister::ic_scalable_misc::{
+    enums::{
+        api_error_type::{ApiError, ApiErrorType},
+        canister_type::CanisterType,
+    },
+    helpers::error_helper::api_error,
+};
+
+use shared::event_models::{CheckIn, Event};
+
+use super::store::{ScalableData, DATA};
+
+// The amount of events that are moved per batch
+const MIGRATION_BATCH_SIZE: usize = 50;
+
+// The time after which a migration that is in progress is considered interrupted and can be resumed (10 minutes)
+const MIGRATION_TIMEOUT: u64 = 10 * 60 * 1_000_000_000;
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub enum EventMigrationSelection {
+    Group(Principal),
+    Identifiers(Vec<Principal>),
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug, PartialEq)]
+pub enum EventMigrationStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed(String),
+}
+
+#[derive(CandidType, Clone, Deserialize, Debug)]
+pub struct EventMigration {
+    pub id: u64,
+    // The child canister the events are moved from
+    pub from: Principal,
+    // The child canister the events are moved to
+    pub to: Principal,
+    pub selection: EventMigrationSelection,
+    // The identifiers that still need to be moved, `None` until the selection is resolved on the source canister
+    pub pending: Option<Vec<Principal>>,
+    // The identifiers that are moved
+    pub migrated: Vec<Principal>,
+    // The identifiers that are not moved because the events hold ticket payments on the source canister
+    #[serde(default)]
+    pub skipped: Vec<Principal>,
+    pub status: EventMigrationStatus,
+    pub updated_at: u64,
+    pub created_at: u64,
+}
+
+impl ScalableData {
+    // Method used to start a migration of events from one child canister to another
+    pub async fn migrate_events(
+        from: Principal,
+        to: Principal,
+        selection: EventMigrationSelection,
+    ) -> Result<EventMigration, ApiError> {
+        let inputs = Some(vec![
+            format!("from - {}", &from.to_string()),
+            format!("to - {}", &to.to_string()),
+        ]);
+
+        if from == to {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "SAME_CANISTER",
+                "The source and target canister are the same",
+                &Self::get_name(),
+                "migrate_events",
+                inputs,
+            ));
+        }
+
+        // check if both canisters are child canisters known to this canister
+        let is_known_child = |principal: &Principal| {
+            DATA.with(|v| {
+                v.borrow()
+                    .canisters
+                    .get(principal)
+                    .is_some_and(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+            })
+        };
+
+        if !is_known_child(&from) || !is_known_child(&to) {
+            return Err(api_error(
+                ApiErrorType::NotFound,
+                "UNKNOWN_CANISTER",
+                "The canister principal isnt known to this canister",
+                &Self::get_name(),
+                "migrate_events",
+                inputs,
+            ));
+        }
+
+        let migration = DATA.with(|v| {
+            let mut data = v.borrow_mut();
+            let id = data.migrations.keys().max().map_or(0, |id| id + 1);
+            let migration = EventMigration {
+                id,
+                from,
+                to,
+                selection,
+                pending: None,
+                migrated: vec![],
+                skipped: vec![],
+                status: EventMigrationStatus::Pending,
+                updated_at: time(),
+                created_at: time(),
+            };
+            data.migrations.insert(id, migration.clone());
+            migration
+        });
+
+        Self::run_migration(migration).await
+    }
+
+    // Method used to resume a migration that failed or got interrupted
+    pub async fn resume_migration(id: u64) -> Result<EventMigration, ApiError> {
+        let inputs = Some(vec![format!("id - {}", &id)]);
+
+        match DATA.with(|v| v.borrow().migrations.get(&id).cloned()) {
+            None => Err(api_error(
+                ApiErrorType::NotFound,
+                "MIGRATION_NOT_FOUND",
+                "No migration found",
+                &Self::get_name(),
+                "resume_migration",
+                inputs,
+            )),
+            Some(_migration) => {
+                if _migration.status == EventMigrationStatus::Completed {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "MIGRATION_COMPLETED",
+                        "The migration is already completed",
+                        &Self::get_name(),
+                        "resume_migration",
+                        inputs,
+                    ));
+                }
+
+                if _migration.status == EventMigrationStatus::InProgress
+                    && time() - _migration.updated_at < MIGRATION_TIMEOUT
+                {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "MIGRATION_IN_PROGRESS",
+                        "The migration is still in progress",
+                        &Self::get_name(),
+                        "resume_migration",
+                        inputs,
+                    ));
+                }
+
+                Self::run_migration(_migration).await
+            }
+        }
+    }
+
+    // Method used to get all the migrations
+    pub fn get_migrations() -> Vec<EventMigration> {
+        DATA.with(|v| v.borrow().migrations.values().cloned().collect())
+    }
+
+    // Method used to run a migration until it is completed or failed, the progress is stored after every batch
+    async fn run_migration(mut migration: EventMigration) -> Result<EventMigration, ApiError> {
+        migration.status = EventMigrationStatus::InProgress;
+        Self::save_migration(&mut migration);
+
+        match Self::process_migration(&mut migration).await {
+            Ok(_) => {
+                migration.status = EventMigrationStatus::Completed;
+                Self::save_migration(&mut migration);
+                Ok(migration)
+            }
+            Err(err) => {
+                migration.status = EventMigrationStatus::Failed(format!("{:?}", err));
+                Self::save_migration(&mut migration);
+                Err(err)
+            }
+        }
+    }
+
+    async fn process_migration(migration: &mut EventMigration) -> Result<(), ApiError> {
+        // Resolve the selection to identifiers on the source canister
+        if migration.pending.is_none() {
+            let identifiers = match &migration.selection {
+                EventMigrationSelection::Identifiers(identifiers) => identifiers.clone(),
+                EventMigrationSelection::Group(group_identifier) => {
+                    let result: Result<(Vec<Principal>,), _> = call::call(
+                        migration.from,
+                        "get_migration_identifiers",
+                        (group_identifier,),
+                    )
+                    .await;
+
+                    match result {
+                        Ok((_identifiers,)) => _identifiers,
+                        Err(err) => {
+                            return Err(Self::migration_call_error(
+                                err.1.as_str(),
+                                "get_migration_identifiers",
+                            ))
+                        }
+                    }
+                }
+            };
+
+            migration.pending = Some(identifiers);
+            Self::save_migration(migration);
+        }
+
+        loop {
+            let batch: Vec<Principal> = migration
+                .pending
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .take(MIGRATION_BATCH_SIZE)
+                .collect();
+
+            if batch.is_empty() {
+                break;
+            }
+
+            // Events with ticket payments stay on the source canister, their payments are held on its subaccounts
+            let blocked = Self::get_migration_blocked_identifiers(migration.from, &batch).await?;
+            if !blocked.is_empty() {
+                if let Some(pending) = migration.pending.as_mut() {
+                    pending.retain(|identifier| !blocked.contains(identifier));
+                }
+                migration.skipped.extend(blocked);
+                Self::save_migration(migration);
+                continue;
+            }
+
+            let moved = Self::migrate_batch(migration.from, migration.to, &batch).await?;
+
+            // Events that changed while being copied are not moved, these are retried on a next run
+            if moved.is_empty() {
+                return Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "MIGRATION_STALLED",
+                    "The events changed while being moved, resume the migration to retry",
+                    &Self::get_name(),
+                    "process_migration",
+                    Some(vec![format!("id - {}", &migration.id)]),
+                ));
+            }
+
+            if let Some(pending) = migration.pending.as_mut() {
+                pending.retain(|identifier| !moved.contains(identifier));
+            }
+            migration.migrated.extend(moved);
+            Self::save_migration(migration);
+        }
+
+        // Once all the events of a group are moved, the source canister no longer stores events of the group
+        if let EventMigrationSelection::Group(group_identifier) = migration.selection {
+            let result: Result<(Vec<Principal>,), _> = call::call(
+                migration.from,
+                "get_migration_identifiers",
+                (group_identifier,),
+            )
+            .await;
+
+            if let Ok((_identifiers,)) = result {
+                if _identifiers.is_empty() {
+                    Self::remove_group_canister(group_identifier, migration.from);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Method used to copy, verify and remove a batch of events, the identifiers that are moved are returned
+    async fn migrate_batch(
+        from: Principal,
+        to: Principal,
+        batch: &Vec<Principal>,
+    ) -> Result<Vec<Principal>, ApiError> {
+        let source_entries = Self::get_migration_entries(from, batch).await?;
+
+        // Identifiers that are no longer on the source canister are already removed by an interrupted run
+        let missing: Vec<Principal> = batch
+            .iter()
+            .filter(|identifier| !source_entries.iter().any(|(_id, _)| &_id == identifier))
+            .cloned()
+            .collect();
+
+        // Copy the events to the target canister
+        if !source_entries.is_empty() {
+            let result: Result<(Result<(), ApiError>,), _> =
+                call::call(to, "import_migration_entries", (source_entries.clone(),)).await;
+
+            match result {
+                Ok((Ok(_),)) => {}
+                Ok((Err(err),)) => return Err(err),
+                Err(err) => {
+                    return Err(Self::migration_call_error(
+                        err.1.as_str(),
+                        "import_migration_entries",
+                    ))
+                }
+            }
+        }
+
+        // Verify the events stored on the target canister against the events of the source canister
+        let target_entries = Self::get_migration_entries(to, batch).await?;
+        for (identifier, event) in source_entries.iter() {
+            let is_verified = target_entries
+                .iter()
+                .find(|(_identifier, _)| _identifier == identifier)
+                .is_some_and(|(_, _event)| _event.fingerprint() == event.fingerprint());
+
+            if !is_verified {
+                return Err(api_error(
+                    ApiErrorType::BadRequest,
+                    "MIGRATION_VERIFICATION_FAILED",
+                    "The event stored on the target canister doesnt match the source",
+                    &Self::get_name(),
+                    "migrate_batch",
+                    Some(vec![format!("identifier - {}", &identifier.to_string())]),
+                ));
+            }
+        }
+
+        for identifier in missing.iter() {
+            if !target_entries
+                .iter()
+                .any(|(_identifier, _)| _identifier == identifier)
+            {
+                return Err(api_error(
+                    ApiErrorType::NotFound,
+                    "EVENT_NOT_FOUND",
+                    "The event is not found on the source or target canister",
+                    &Self::get_name(),
+                    "migrate_batch",
+                    Some(vec![format!("identifier - {}", &identifier.to_string())]),
+                ));
+            }
+        }
+
+        // Point the identifiers to the target canister before they are removed from the source canister
+        DATA.with(|v| {
+            let mut data = v.borrow_mut();
+            batch.iter().for_each(|identifier| {
+                data.relocated_events.insert(*identifier, to);
+            });
+        });
+
+        source_entries.iter().for_each(|(_, event)| {
+            Self::add_group_canister(event.group_identifier, to);
+        });
+
+        // Remove the verified events from the source canister
+        let removable: Vec<(Principal, String)> = source_entries
+            .iter()
+            .map(|(identifier, event)| (*identifier, event.fingerprint()))
+            .collect();
+
+        let result: Result<(Vec<Principal>,), _> =
+            call::call(from, "remove_migration_entries", (removable,)).await;
+
+        let removed = match result {
+            Ok((_removed,)) => _removed,
+            Err(err) => {
+                return Err(Self::migration_call_error(
+                    err.1.as_str(),
+                    "remove_migration_entries",
+                ))
+            }
+        };
+
+        // Events that are still on the source canister changed in the meantime, point them back to the source canister
+        let unchanged: Vec<Principal> = source_entries
+            .iter()
+            .filter(|(identifier, _)| !removed.contains(identifier))
+            .map(|(identifier, _)| *identifier)
+            .collect();
+
+        if !unchanged.is_empty() {
+            DATA.with(|v| {
+                let mut data = v.borrow_mut();
+                unchanged.iter().for_each(|identifier| {
+                    data.relocated_events.insert(*identifier, from);
+                });
+            });
+
+            // and remove their copies from the target canister
+            let result: Result<((),), _> =
+                call::call(to, "discard_migration_entries", (unchanged,)).await;
+
+            if let Err(err) = result {
+                return Err(Self::migration_call_error(
+                    err.1.as_str(),
+                    "discard_migration_entries",
+                ));
+            }
+        }
+
+        // Move the check-ins of the removed events, no check-ins are added on the source canister once an event is removed
+        // identifiers that were already removed by an interrupted run are included, their check-ins could still be on the source canister
+        let moved = [removed, missing].concat();
+        Self::migrate_check_ins(from, to, &moved).await?;
+
+        Ok(moved)
+    }
+
+    // Method used to copy the check-ins of moved events to the target canister and then remove them from the source canister
+    async fn migrate_check_ins(
+        from: Principal,
+        to: Principal,
+        identifiers: &Vec<Principal>,
+    ) -> Result<(), ApiError> {
+        if identifiers.is_empty() {
+            return Ok(());
+        }
+
+        let result: Result<(Vec<CheckIn>,), _> =
+            call::call(from, "get_migration_check_ins", (identifiers,)).await;
+
+        let check_ins = match result {
+            Ok((_check_ins,)) => _check_ins,
+            Err(err) => {
+                return Err(Self::migration_call_error(
+                    err.1.as_str(),
+                    "get_migration_check_ins",
+                ))
+            }
+        };
+
+        if !check_ins.is_empty() {
+            let result: Result<((),), _> =
+                call::call(to, "import_migration_check_ins", (check_ins,)).await;
+
+            if let Err(err) = result {
+                return Err(Self::migration_call_error(
+                    err.1.as_str(),
+                    "import_migration_check_ins",
+                ));
+            }
+        }
+
+        let result: Result<((),), _> =
+            call::call(from, "remove_migration_check_ins", (identifiers,)).await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Self::migration_call_error(
+                err.1.as_str(),
+                "remove_migration_check_ins",
+            )),
+        }
+    }
+
+    // Inter canister call to fetch the stored events from a child canister
+    async fn get_migration_entries(
+        canister_principal: Principal,
+        identifiers: &Vec<Principal>,
+    ) -> Result<Vec<(Principal, Event)>, ApiError> {
+        let result: Result<(Vec<(Principal, Event)>,), _> =
+            call::call(canister_principal, "get_migration_entries", (identifiers,)).await;
+
+        match result {
+            Ok((_entries,)) => Ok(_entries),
+            Err(err) => Err(Self::migration_call_error(
+                err.1.as_str(),
+                "get_migration_entries",
+            )),
+        }
+    }
+
+    // Inter canister call to fetch the identifiers of the events that can't be moved from a child canister
+    async fn get_migration_blocked_identifiers(
+        canister_principal: Principal,
+        identifiers: &Vec<Principal>,
+    ) -> Result<Vec<Principal>, ApiError> {
+        let result: Result<(Vec<Principal>,), _> = call::call(
+            canister_principal,
+            "get_migration_blocked_identifiers",
+            (identifiers,),
+        )
+        .await;
+
+        match result {
+            Ok((_identifiers,)) => Ok(_identifiers),
+            Err(err) => Err(Self::migration_call_error(
+                err.1.as_str(),
+                "get_migration_blocked_identifiers",
+            )),
+        }
+    }
+
+    fn save_migration(migration: &mut EventMigration) {
+        migration.updated_at = time();
+        DATA.with(|v| {
+            v.borrow_mut()
+                .migrations
+                .insert(migration.id, migration.clone())
+        });
+    }
+
+    fn migration_call_error(message: &str, method_name: &str) -> ApiError {
+        api_error(
+            ApiErrorType::BadRequest,
+            "MIGRATION_CALL_FAILED",
+            message,
+            &Self::get_name(),
+            method_name,
+            None,
+        )
+    }
+}
@@ -12,12 +12,20 @@ use ic_scalable_canister::ic_scalable_misc::{
     },
 };
 
-use super::store::{ScalableData, DATA};
+use shared::event_models::MetadataSchema;
+
+use super::{
+    methods::is_controller,
+    store::{ScalableData, DATA},
+};
 
 // Method to retrieve an available canister to write updated to
+// the canister that already stores events of the group is preferred
 #[query]
-fn get_available_canister() -> Result<ScalableCanisterDetails, String> {
-    ScalableData::get_available_canister(caller())
+fn get_available_canister(
+    group_identifier: Option<Principal>,
+) -> Result<ScalableCanisterDetails, String> {
+    ScalableData::get_available_canister(caller(), group_identifier)
 }
 
 // Methods to retrieve all the canisters
@@ -27,6 +35,7 @@ fn get_canisters() -> Vec<ScalableCanisterDetails> {
 }
 
 // Method called by child canister once full (inter-canister call)
+// the identifier of the entry on the new canister is returned, it contains the principal of the new canister
 // can only be called by a child canister
 #[update]
 async fn close_child_canister_and_spawn_sibling(
@@ -36,6 +45,35 @@ async fn close_child_canister_and_spawn_sibling(
     ScalableData::close_child_canister_and_spawn_sibling(caller(), last_entry_id, entry).await
 }
 
+// Method called by child canister when it stores events of a group (inter-canister call)
+// can only be called by a child canister
+#[update]
+fn register_group_canister(group_identifier: Principal) -> Result<(), ApiError> {
+    ScalableData::register_group_canister(caller(), group_identifier)
+}
+
+// Method called by child canister to get the secret the check-in codes are signed with (inter-canister call)
+// can only be called by a child canister, all the child canisters share the secret
+#[update]
+async fn get_check_in_secret() -> Result<Vec<u8>, ApiError> {
+    ScalableData::get_check_in_secret(caller()).await
+}
+
+// Method called by child canister when the metadata schema of a group is registered (inter-canister call)
+// can only be called by a child canister, the schema is pushed to all the child canisters
+// the canisters that failed are returned in the error, the registration can be retried
+#[update]
+async fn register_metadata_schema(schema: MetadataSchema) -> Result<(), ApiError> {
+    ScalableData::register_metadata_schema(caller(), schema).await
+}
+
+// Method to rebuild the group to canister mapping from the groups stored on the child canisters
+// also runs after an upgrade, until all the child canisters are synced their groups fall back to all the child canisters
+#[update(guard = "is_controller")]
+async fn sync_group_canisters() -> Result<(), ApiError> {
+    ScalableData::sync_group_canisters().await
+}
+
 // Method to retrieve the latest wasm version of the child canister that is currently stored
 #[query]
 fn get_latest_wasm_version() -> WasmVersion {
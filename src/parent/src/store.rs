@@ -1,8 +1,11 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
 
 use candid::{CandidType, Deserialize, Principal};
 use ic_cdk::{
-    api::{call, time},
+    api::{call, management_canister::main::raw_rand, time},
     id,
 };
 
@@ -10,26 +13,35 @@ use ic_scalable_canister::ic_scalable_misc::{
     enums::{
         api_error_type::{ApiError, ApiErrorType},
         canister_type::CanisterType,
-        filter_type::FilterType,
-        sort_type::SortDirection,
         wasm_version_type::WasmVersion,
     },
     helpers::{
         canister_helper::{Canister, CanisterID, CanisterSettings, InstallCodeMode},
         error_helper::api_error,
         logger_helper::add_log,
-        paging_helper::get_paged_data,
         serialize_helper::deserialize,
     },
     models::{
         canister_models::ScalableCanisterDetails,
+        identifier_model::Identifier,
         logger_models::{LogType, PostLog},
         paged_response_models::PagedResponse,
         wasm_models::WasmDetails,
     },
 };
 
-use shared::event_models::{EventFilter, EventResponse, EventSort};
+use shared::{
+    chunk_models::{ChunkSnapshot, ChunkedData, ChunkedDataRequest},
+    event_facets::{EventFacetCounter, EventFacetRequest},
+    event_models::{
+        Event, EventFilterExpression, EventResponse, EventSort, EventsCount, EventsCountBreakdown,
+        EventsSlice, MetadataSchema, PagedEventsResponse,
+    },
+    event_sort::compare_events,
+    IDENTIFIER_KIND,
+};
+
+use super::migration::EventMigration;
 
 #[derive(CandidType, Clone, Deserialize)]
 pub struct ScalableMetaData {
@@ -58,6 +70,27 @@ pub struct ScalableData {
     pub updated_at: u64,
     // created_at record
     pub created_at: u64,
+    // The events that are moved to another child canister than the one their identifier resolves to
+    #[serde(default)]
+    pub relocated_events: HashMap<Principal, Principal>,
+    // The migrations of events between child canisters
+    #[serde(default)]
+    pub migrations: HashMap<u64, EventMigration>,
+    // The child canisters that store events of a group
+    #[serde(default)]
+    pub group_canisters: HashMap<Principal, Vec<Principal>>,
+    // The child canisters of which all the groups are known in the group canisters
+    #[serde(default)]
+    pub group_canisters_synced: HashSet<Principal>,
+    // The metadata schemas of the groups, pushed to all the child canisters
+    #[serde(default)]
+    pub metadata_schemas: HashMap<Principal, MetadataSchema>,
+    // The event attendee canister the ticket holders are recorded on, pushed to all the child canisters
+    #[serde(default)]
+    pub event_attendee_canister: Option<Principal>,
+    // The secret the check-in codes are signed with, shared by all the child canisters
+    #[serde(default)]
+    pub check_in_secret: Vec<u8>,
 }
 
 impl Default for ScalableData {
@@ -69,25 +102,49 @@ impl Default for ScalableData {
             parent: Principal::anonymous(),
             updated_at: time(),
             created_at: time(),
+            relocated_events: HashMap::new(),
+            migrations: HashMap::new(),
+            group_canisters: HashMap::new(),
+            group_canisters_synced: HashSet::new(),
+            metadata_schemas: HashMap::new(),
+            event_attendee_canister: None,
+            check_in_secret: vec![],
         }
     }
 }
 
+// The amount of times the chunks of a child canister are fetched when the snapshot expires in between the chunk calls
+static MAX_SNAPSHOT_ATTEMPTS: usize = 3;
+
 thread_local! {
     pub static DATA: RefCell<ScalableData> = RefCell::new(ScalableData::default());
 }
 
 impl ScalableData {
     // Method to retrieve an available canister to write updates to
-    pub fn get_available_canister(caller: Principal) -> Result<ScalableCanisterDetails, String> {
+    // if a group is passed, the canister that already stores events of the group is preferred
+    pub fn get_available_canister(
+        caller: Principal,
+        group_identifier: Option<Principal>,
+    ) -> Result<ScalableCanisterDetails, String> {
         let canister = DATA.with(|v| {
-            v.borrow()
-                .canisters
-                .iter()
-                // filter out self in case this method is called by a child canister
-                .filter(|(_, c)| c.principal != caller)
-                .find(|(_, c)| c.is_available)
-                .map(|(_, details)| details.clone())
+            let data = v.borrow();
+            // filter out self in case this method is called by a child canister
+            let is_available =
+                |c: &&ScalableCanisterDetails| c.principal != caller && c.is_available;
+
+            let group_canister = group_identifier
+                .and_then(|_group_identifier| data.group_canisters.get(&_group_identifier))
+                .and_then(|principals| {
+                    principals
+                        .iter()
+                        .filter_map(|principal| data.canisters.get(principal))
+                        .find(&is_available)
+                });
+
+            group_canister
+                .or_else(|| data.canisters.values().find(&is_available))
+                .cloned()
         });
 
         match canister {
@@ -108,6 +165,354 @@ impl ScalableData {
         return canisters;
     }
 
+    // Method called by a child canister when it stores events of a group (inter-canister call)
+    pub fn register_group_canister(
+        caller: Principal,
+        group_identifier: Principal,
+    ) -> Result<(), ApiError> {
+        if DATA.with(|v| !v.borrow().canisters.contains_key(&caller)) {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "UNKNOWN_CANISTER",
+                "The caller principal isnt known to this canister",
+                &Self::get_name(),
+                "register_group_canister",
+                Some(vec![format!("caller - {}", &caller.to_string())]),
+            ));
+        }
+
+        Self::add_group_canister(group_identifier, caller);
+        Ok(())
+    }
+
+    // Method called by a child canister when the metadata schema of a group is registered (inter-canister call)
+    // the schema is stored and pushed to all the child canisters, the canisters that failed are returned in the error
+    // pushing the schema is idempotent so the registration can be retried
+    pub async fn register_metadata_schema(
+        caller: Principal,
+        schema: MetadataSchema,
+    ) -> Result<(), ApiError> {
+        if DATA.with(|v| !v.borrow().canisters.contains_key(&caller)) {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "UNKNOWN_CANISTER",
+                "The caller principal isnt known to this canister",
+                &Self::get_name(),
+                "register_metadata_schema",
+                Some(vec![format!("caller - {}", &caller.to_string())]),
+            ));
+        }
+
+        DATA.with(|v| {
+            v.borrow_mut()
+                .metadata_schemas
+                .insert(schema.group_identifier, schema.clone())
+        });
+
+        let canisters: Vec<Principal> = Self::get_canisters()
+            .into_iter()
+            .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+            .map(|c| c.principal)
+            .collect();
+
+        let mut failed: Vec<String> = vec![];
+        for canister in canisters {
+            if let Err(err) = Self::push_metadata_schema(canister, &schema).await {
+                failed.push(err);
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_PUSH_SCHEMA",
+                "Failed to push the metadata schema to one or more child canisters",
+                &Self::get_name(),
+                "register_metadata_schema",
+                Some(failed),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Method used to get the metadata schema of a group
+    pub fn get_metadata_schema(group_identifier: Principal) -> Option<MetadataSchema> {
+        DATA.with(|v| v.borrow().metadata_schemas.get(&group_identifier).cloned())
+    }
+
+    // Method used to configure the event attendee canister, it is pushed to all the child canisters
+    // the canisters that failed are returned in the error, the child canisters fetch it when it isnt pushed
+    pub async fn set_event_attendee_canister(
+        event_attendee_canister: Principal,
+    ) -> Result<(), ApiError> {
+        DATA.with(|v| v.borrow_mut().event_attendee_canister = Some(event_attendee_canister));
+
+        let canisters: Vec<Principal> = Self::get_canisters()
+            .into_iter()
+            .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+            .map(|c| c.principal)
+            .collect();
+
+        let mut failed: Vec<String> = vec![];
+        for canister in canisters {
+            let result: Result<(), _> = call::call(
+                canister,
+                "set_event_attendee_canister_by_parent",
+                (event_attendee_canister,),
+            )
+            .await;
+
+            if let Err(err) = result {
+                failed.push(format!("canister - {} - {}", &canister.to_string(), err.1));
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_PUSH_EVENT_ATTENDEE_CANISTER",
+                "Failed to push the event attendee canister to one or more child canisters",
+                &Self::get_name(),
+                "set_event_attendee_canister",
+                Some(failed),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Method used to get the configured event attendee canister
+    pub fn get_event_attendee_canister() -> Option<Principal> {
+        DATA.with(|v| v.borrow().event_attendee_canister)
+    }
+
+    // Method called by a child canister to get the secret the check-in codes are signed with (inter-canister call)
+    // a random secret is generated on the first use, the child canisters share it so the codes stay valid when events are migrated
+    pub async fn get_check_in_secret(caller: Principal) -> Result<Vec<u8>, ApiError> {
+        if DATA.with(|v| !v.borrow().canisters.contains_key(&caller)) {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "UNKNOWN_CANISTER",
+                "The caller principal isnt known to this canister",
+                &Self::get_name(),
+                "get_check_in_secret",
+                Some(vec![format!("caller - {}", &caller.to_string())]),
+            ));
+        }
+
+        let secret = DATA.with(|v| v.borrow().check_in_secret.clone());
+        if !secret.is_empty() {
+            return Ok(secret);
+        }
+
+        match raw_rand().await {
+            Ok((_secret,)) => {
+                // Another call could have generated the secret during the inter-canister call
+                Ok(DATA.with(|v| {
+                    let mut data = v.borrow_mut();
+                    if data.check_in_secret.is_empty() {
+                        data.check_in_secret = _secret;
+                    }
+                    data.check_in_secret.clone()
+                }))
+            }
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "SECRET_GENERATION_FAILED",
+                err.1.as_str(),
+                &Self::get_name(),
+                "get_check_in_secret",
+                None,
+            )),
+        }
+    }
+
+    // Send a metadata schema to a child canister (inter-canister call)
+    async fn push_metadata_schema(
+        canister: Principal,
+        schema: &MetadataSchema,
+    ) -> Result<(), String> {
+        let result: Result<(), _> =
+            call::call(canister, "set_metadata_schema_by_parent", (schema,)).await;
+
+        result.map_err(|err| format!("canister - {} - {}", &canister.to_string(), err.1))
+    }
+
+    // Method used to get the child canisters that store events of a group
+    // if no group is passed, the group is unknown or the groups of a child canister aren't synced yet, all the child canisters are returned
+    pub fn get_group_canisters(group_identifier: Option<Principal>) -> Vec<Principal> {
+        DATA.with(|v| {
+            let data = v.borrow();
+            let is_synced = data
+                .canisters
+                .values()
+                .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+                .all(|c| data.group_canisters_synced.contains(&c.principal));
+
+            match group_identifier
+                .filter(|_| is_synced)
+                .and_then(|_group_identifier| data.group_canisters.get(&_group_identifier))
+            {
+                Some(principals) => principals.clone(),
+                None => data.canisters.keys().cloned().collect(),
+            }
+        })
+    }
+
+    // Method used to rebuild the group to canister mapping from the groups stored on the child canisters
+    // a child canister is marked as synced once its groups are fetched, the canisters that failed are returned in the error
+    pub async fn sync_group_canisters() -> Result<(), ApiError> {
+        let canisters: Vec<Principal> = DATA.with(|v| {
+            v.borrow()
+                .canisters
+                .values()
+                .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+                .map(|c| c.principal)
+                .collect()
+        });
+
+        let mut failed: Vec<String> = vec![];
+        for canister in canisters {
+            let result: Result<(Vec<Principal>,), _> =
+                call::call(canister, "get_group_identifiers", ()).await;
+
+            match result {
+                Ok((_group_identifiers,)) => {
+                    _group_identifiers
+                        .into_iter()
+                        .for_each(|_group_identifier| {
+                            Self::add_group_canister(_group_identifier, canister)
+                        });
+                    DATA.with(|v| v.borrow_mut().group_canisters_synced.insert(canister));
+                }
+                Err(err) => {
+                    failed.push(format!("canister - {} - {}", &canister.to_string(), err.1))
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_FETCH_GROUPS",
+                "Failed to fetch the groups of one or more child canisters",
+                &Self::get_name(),
+                "sync_group_canisters",
+                Some(failed),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn add_group_canister(group_identifier: Principal, canister: Principal) {
+        DATA.with(|v| {
+            let mut data = v.borrow_mut();
+            let principals = data.group_canisters.entry(group_identifier).or_default();
+            if !principals.contains(&canister) {
+                principals.push(canister);
+            }
+        });
+    }
+
+    pub(crate) fn remove_group_canister(group_identifier: Principal, canister: Principal) {
+        DATA.with(|v| {
+            if let Some(principals) = v.borrow_mut().group_canisters.get_mut(&group_identifier) {
+                principals.retain(|principal| principal != &canister);
+            }
+        });
+    }
+
+    // Method to resolve the child canister that stores the event for the given identifier
+    pub fn resolve_event_canister(identifier: Principal) -> Result<Principal, ApiError> {
+        let inputs = Some(vec![format!("identifier - {}", &identifier.to_string())]);
+
+        // Check if the identifier is an event identifier
+        let (_, _canister, _kind) = Identifier::decode(&identifier);
+        if IDENTIFIER_KIND != _kind {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "INVALID_IDENTIFIER",
+                "The identifier is not an event identifier",
+                &Self::get_name(),
+                "resolve_event_canister",
+                inputs,
+            ));
+        }
+
+        // Check if the event is moved to another canister
+        if let Some(_canister) =
+            DATA.with(|v| v.borrow().relocated_events.get(&identifier).cloned())
+        {
+            return Ok(_canister);
+        }
+
+        // The identifier is encoded with the child canister that created the event
+        let canister = DATA.with(|v| {
+            v.borrow()
+                .canisters
+                .get(&_canister)
+                .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+                .map(|c| c.principal)
+        });
+
+        match canister {
+            None => Err(api_error(
+                ApiErrorType::NotFound,
+                "CANISTER_NOT_FOUND",
+                "No child canister found for this identifier",
+                &Self::get_name(),
+                "resolve_event_canister",
+                inputs,
+            )),
+            Some(_canister) => Ok(_canister),
+        }
+    }
+
+    // Method used to get events by their identifiers from the child canisters that store them
+    // requires composite queries to be released to mainnet
+    pub async fn get_events_by_identifiers(
+        identifiers: Vec<Principal>,
+    ) -> Vec<(Principal, Result<EventResponse, ApiError>)> {
+        let mut events: Vec<(Principal, Result<EventResponse, ApiError>)> = vec![];
+
+        for identifier in identifiers {
+            let event = match Self::resolve_event_canister(identifier) {
+                Err(err) => Err(err),
+                Ok(_canister) => Self::get_child_event(_canister, identifier).await,
+            };
+            events.push((identifier, event));
+        }
+
+        events
+    }
+
+    // Inter canister call to fetch a single event from the child canister
+    async fn get_child_event(
+        canister_principal: Principal,
+        identifier: Principal,
+    ) -> Result<EventResponse, ApiError> {
+        let result: Result<(Result<EventResponse, ApiError>,), _> = call::call(
+            canister_principal,
+            "get_event",
+            (identifier, None::<Principal>),
+        )
+        .await;
+
+        match result {
+            Ok((_event,)) => _event,
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_FETCH_EVENT",
+                err.1.as_str(),
+                &Self::get_name(),
+                "get_child_event",
+                Some(vec![format!("identifier - {}", &identifier.to_string())]),
+            )),
+        }
+    }
+
     // Method used on the init function to spawn a child canister when the parent canister is installed
     pub async fn initialize_first_child_canister() -> () {
         // check if the child wasm is present
@@ -187,6 +592,34 @@ impl ScalableData {
                         match installed_canister {
                             Err(err) => Err(err),
                             Ok(new_installed_canister_principal) => {
+                                // the new canister needs the metadata schemas of the groups before it stores events
+                                let schemas: Vec<MetadataSchema> = DATA.with(|v| {
+                                    v.borrow().metadata_schemas.values().cloned().collect()
+                                });
+
+                                let mut failed: Vec<String> = vec![];
+                                for schema in schemas {
+                                    if let Err(err) = Self::push_metadata_schema(
+                                        new_installed_canister_principal,
+                                        &schema,
+                                    )
+                                    .await
+                                    {
+                                        failed.push(err);
+                                    }
+                                }
+
+                                if !failed.is_empty() {
+                                    return Err(api_error(
+                                        ApiErrorType::BadRequest,
+                                        "FAILED_TO_PUSH_SCHEMA",
+                                        "Failed to push the metadata schemas to the new canister",
+                                        &Self::get_name(),
+                                        "close_child_canister_and_spawn_sibling",
+                                        Some(failed),
+                                    ));
+                                }
+
                                 // update the caller canister
                                 _caller_canister.is_available = false;
                                 _caller_canister.entry_range = (0, Some(last_entry_id));
@@ -197,13 +630,22 @@ impl ScalableData {
                                         .insert(_caller_canister.principal, _caller_canister)
                                 });
 
-                                // send the entry to the new canister
-                                let call_result: Result<(Result<(), ApiError>,), _> = call::call(
-                                    new_installed_canister_principal,
-                                    "add_entry_by_parent",
-                                    (entry,),
-                                )
-                                .await;
+                                // the group of the entry is now (also) stored on the new canister
+                                if let Ok(_event) = deserialize::<Event>(entry.clone()) {
+                                    Self::add_group_canister(
+                                        _event.group_identifier,
+                                        new_installed_canister_principal,
+                                    );
+                                }
+
+                                // send the entry to the new canister, only the caller can finalize it there
+                                let call_result: Result<(Result<Principal, ApiError>,), _> =
+                                    call::call(
+                                        new_installed_canister_principal,
+                                        "add_entry_by_parent",
+                                        (entry, Some(caller)),
+                                    )
+                                    .await;
 
                                 match call_result {
                                     Err(err) => Err(api_error(
@@ -214,7 +656,9 @@ impl ScalableData {
                                         "close_child_canister_and_spawn_sibling",
                                         inputs,
                                     )),
-                                    Ok(_) => Ok(new_installed_canister_principal),
+                                    Ok((Err(err),)) => Err(err),
+                                    // the identifier of the entry contains the principal of the new canister
+                                    Ok((Ok(_identifier),)) => Ok(_identifier),
                                 }
                             }
                         }
@@ -352,6 +796,9 @@ impl ScalableData {
             ));
         }
 
+        // a freshly installed canister has no events, so all its groups are known
+        let is_fresh_install = matches!(install_code_mode, InstallCodeMode::Install);
+
         let install_canister = Canister::from(canister_principal)
             .install_code(
                 install_code_mode,
@@ -379,9 +826,11 @@ impl ScalableData {
                 };
 
                 DATA.with(|v| {
-                    v.borrow_mut()
-                        .canisters
-                        .insert(canister_principal, new_child_details)
+                    let mut data = v.borrow_mut();
+                    data.canisters.insert(canister_principal, new_child_details);
+                    if is_fresh_install {
+                        data.group_canisters_synced.insert(canister_principal);
+                    }
                 });
                 Ok(canister_principal)
             }
@@ -443,91 +892,278 @@ impl ScalableData {
 
     // Method used to get all the events from the child canisters filtered, sorted and paged
     // requires composite queries to be released to mainnet
+    // if a group is passed, only the child canisters that store events of the group are queried
+    // the requested facets are counted on the child canisters and summed
     pub async fn get_child_canister_data(
         limit: usize,
         page: usize,
-        filters: Vec<EventFilter>,
-        filter_type: FilterType,
-        sort: EventSort,
-    ) -> PagedResponse<EventResponse> {
-        let canisters: Vec<Principal> = DATA.with(|data| {
-            data.borrow()
+        filter: EventFilterExpression,
+        sort: Vec<EventSort>,
+        group_identifier: Option<Principal>,
+        facets: Option<EventFacetRequest>,
+    ) -> Result<PagedEventsResponse, ApiError> {
+        let canisters = Self::get_group_canisters(group_identifier);
+        let page = page.max(1);
+
+        // Every child canister only returns its first `page * limit` events in the requested order
+        let request = ChunkedDataRequest {
+            filter,
+            group_identifier,
+            sort,
+            max_entries: page * limit,
+            facets,
+        };
+
+        let mut total: usize = 0;
+        let mut slices: Vec<Vec<EventResponse>> = vec![];
+        let mut facet_counter = request.facets.as_ref().map(EventFacetCounter::new);
+        for canister in canisters {
+            let slice = Self::get_filtered_child_data(canister, &request).await?;
+            total += slice.total;
+            slices.push(slice.events);
+
+            if let (Some(counter), Some(_facets)) = (facet_counter.as_mut(), slice.facets) {
+                counter.merge(_facets);
+            }
+        }
+
+        let merged_events = Self::merge_ordered_events(slices, &request.sort, request.max_entries);
+        let data: Vec<EventResponse> = merged_events
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+
+        Ok(PagedEventsResponse {
+            events: PagedResponse {
+                total,
+                number_of_pages: match limit {
+                    0 => 0,
+                    _ => total.div_ceil(limit),
+                },
+                page,
+                limit,
+                data,
+            },
+            facets: facet_counter.map(|counter| counter.into_facets()),
+        })
+    }
+
+    // Method used to get the events count for a set of groups from all the child canisters
+    // requires composite queries to be released to mainnet
+    pub async fn get_events_count(
+        group_identifiers: Vec<Principal>,
+        with_breakdown: bool,
+    ) -> Result<Vec<(Principal, EventsCount)>, ApiError> {
+        let canisters: Vec<Principal> = DATA.with(|v| {
+            v.borrow()
                 .canisters
-                .clone()
-                .into_iter()
-                .map(|c| c.1.principal.clone())
+                .values()
+                .filter(|c| matches!(c.canister_type, CanisterType::ScalableChild))
+                .map(|c| c.principal)
                 .collect()
         });
 
-        let mut events: Vec<EventResponse> = vec![];
+        let mut events_counts: HashMap<Principal, EventsCount> = group_identifiers
+            .iter()
+            .map(|group_identifier| {
+                (
+                    *group_identifier,
+                    EventsCount {
+                        total: 0,
+                        breakdown: match with_breakdown {
+                            true => Some(EventsCountBreakdown::default()),
+                            false => None,
+                        },
+                    },
+                )
+            })
+            .collect();
+
         for canister in canisters {
-            let mut canister_data =
-                Self::get_filtered_child_data(canister, &filters, &filter_type).await;
-            events.append(&mut canister_data);
+            let result: Result<(Vec<(Principal, EventsCount)>,), _> = call::call(
+                canister,
+                "get_events_count_breakdown",
+                (&group_identifiers,),
+            )
+            .await;
+
+            match result {
+                Err(err) => {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "FAILED_TO_FETCH_COUNTS",
+                        err.1.as_str(),
+                        &Self::get_name(),
+                        "get_events_count",
+                        Some(vec![format!("canister - {}", &canister.to_string())]),
+                    ))
+                }
+                Ok((_counts,)) => {
+                    // Merge the counts of the child canister with the counts of the other child canisters
+                    for (group_identifier, count) in _counts {
+                        if let Some(existing_value) = events_counts.get_mut(&group_identifier) {
+                            existing_value.total += count.total;
+
+                            if let (Some(breakdown), Some(_breakdown)) =
+                                (existing_value.breakdown.as_mut(), count.breakdown)
+                            {
+                                breakdown.upcoming += _breakdown.upcoming;
+                                breakdown.past += _breakdown.past;
+                                breakdown.cancelled += _breakdown.cancelled;
+                            }
+                        }
+                    }
+                }
+            }
         }
 
-        let ordered_events = Self::get_ordered_events(events, sort);
-        get_paged_data(ordered_events, limit, page)
+        Ok(events_counts.into_iter().collect())
+    }
+
+    // Method used to get the amount of live events per tag for a group from the child canisters that store events of the group
+    // requires composite queries to be released to mainnet
+    pub async fn get_tag_counts(
+        group_identifier: Principal,
+    ) -> Result<Vec<(u32, usize)>, ApiError> {
+        let mut tag_counts: HashMap<u32, usize> = HashMap::new();
+
+        for canister in Self::get_group_canisters(Some(group_identifier)) {
+            let result: Result<(Vec<(u32, usize)>,), _> =
+                call::call(canister, "get_tag_counts", (group_identifier,)).await;
+
+            match result {
+                Err(err) => {
+                    return Err(api_error(
+                        ApiErrorType::BadRequest,
+                        "FAILED_TO_FETCH_TAG_COUNTS",
+                        err.1.as_str(),
+                        &Self::get_name(),
+                        "get_tag_counts",
+                        Some(vec![format!("canister - {}", &canister.to_string())]),
+                    ))
+                }
+                Ok((_tag_counts,)) => {
+                    for (tag, count) in _tag_counts {
+                        *tag_counts.entry(tag).or_insert(0) += count;
+                    }
+                }
+            }
+        }
+
+        Ok(tag_counts.into_iter().collect())
     }
 
     // Method to get the data from a single child canister in chunks
     // requires composite queries to be released to mainnet
+    // when the data on the child canister changes between the chunk calls the snapshot expires and the chunks are fetched again
     async fn get_filtered_child_data(
         canister_principal: Principal,
-        filters: &Vec<EventFilter>,
-        filter_type: &FilterType,
-    ) -> Vec<EventResponse> {
-        // Do initial fetch of the first chunk and determine the number of chunks
-        let (mut bytes, (_, last)) =
-            Self::get_chunked_child_data(canister_principal, filters, filter_type, 0, None).await;
-
-        // If there are more chunks, fetch them
-        if last > 1 {
-            for i in 1..last + 1 {
-                let (mut _bytes, _) =
-                    Self::get_chunked_child_data(canister_principal, filters, filter_type, i, None)
-                        .await;
-                // Append the bytes to the first chunk
-                bytes.append(&mut _bytes);
+        request: &ChunkedDataRequest,
+    ) -> Result<EventsSlice, ApiError> {
+        let mut attempt: usize = 1;
+        loop {
+            match Self::get_snapshot_child_data(canister_principal, request).await {
+                // The data changed while fetching the chunks, start over with a new snapshot
+                Err(ApiError::BadRequest(message))
+                    if message.tag == "SNAPSHOT_EXPIRED" && attempt < MAX_SNAPSHOT_ATTEMPTS =>
+                {
+                    attempt += 1;
+                }
+                result => return result,
             }
         }
+    }
 
-        // Deserialize the bytes to the correct data type
-        match deserialize::<Vec<EventResponse>>(bytes.clone()) {
-            Ok(_res) => _res,
-            Err(_err) => {
-                ic_cdk::println!("Error: {}", _err);
-                vec![]
-            }
+    // Method to fetch all the chunks of a single snapshot, verify the reassembled bytes and deserialize them
+    async fn get_snapshot_child_data(
+        canister_principal: Principal,
+        request: &ChunkedDataRequest,
+    ) -> Result<EventsSlice, ApiError> {
+        // Do initial fetch of the first chunk, this creates the snapshot and determines the number of chunks
+        let first =
+            Self::get_chunked_child_data(canister_principal, request, None, 0, None).await?;
+        let snapshot = first.snapshot;
+        let mut bytes = first.bytes;
+
+        // If there are more chunks, fetch them using the same snapshot
+        for chunk in 1..first.total_chunks {
+            let mut _data = Self::get_chunked_child_data(
+                canister_principal,
+                request,
+                Some(snapshot.clone()),
+                chunk,
+                None,
+            )
+            .await?;
+            // Append the bytes to the first chunk
+            bytes.append(&mut _data.bytes);
         }
+
+        let inputs = Some(vec![format!(
+            "canister - {}",
+            &canister_principal.to_string()
+        )]);
+
+        // Verify the reassembled bytes against the snapshot
+        if bytes.len() != snapshot.total_bytes
+            || ChunkSnapshot::checksum(&bytes) != snapshot.checksum
+        {
+            return Err(api_error(
+                ApiErrorType::BadRequest,
+                "SNAPSHOT_EXPIRED",
+                "The reassembled chunks do not match the snapshot",
+                &Self::get_name(),
+                "get_filtered_child_data",
+                inputs,
+            ));
+        }
+
+        // Deserialize the bytes to the correct data type
+        deserialize::<EventsSlice>(bytes).map_err(|err| {
+            api_error(
+                ApiErrorType::BadRequest,
+                "DESERIALIZE_FAILED",
+                err.to_string().as_str(),
+                &Self::get_name(),
+                "get_filtered_child_data",
+                inputs,
+            )
+        })
     }
 
-    // Method to get ordered events
-    fn get_ordered_events(mut events: Vec<EventResponse>, sort: EventSort) -> Vec<EventResponse> {
-        use EventSort::*;
-        use SortDirection::*;
-        match sort {
-            CreatedOn(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.created_on.cmp(&b.created_on)),
-                Desc => events.sort_by(|a, b| b.created_on.cmp(&a.created_on)),
-            },
-            UpdatedOn(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.updated_on.cmp(&b.updated_on)),
-                Desc => events.sort_by(|a, b| b.updated_on.cmp(&a.updated_on)),
-            },
-            StartDate(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.date.start_date.cmp(&b.date.start_date)),
-                Desc => events.sort_by(|a, b| b.date.start_date.cmp(&a.date.start_date)),
-            },
-            EndDate(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.date.end_date.cmp(&b.date.end_date)),
-                Desc => events.sort_by(|a, b| b.date.end_date.cmp(&a.date.end_date)),
-            },
-            AttendeeCount(direction) => match direction {
-                Asc => events.sort_by(|a, b| a.attendee_count.cmp(&b.attendee_count)),
-                Desc => events.sort_by(|a, b| b.attendee_count.cmp(&a.attendee_count)),
-            },
-        };
+    // Method to merge the ordered events of the child canisters (k-way merge)
+    // only the first `max_entries` events are merged
+    fn merge_ordered_events(
+        slices: Vec<Vec<EventResponse>>,
+        sort: &[EventSort],
+        max_entries: usize,
+    ) -> Vec<EventResponse> {
+        let mut slices: Vec<std::vec::IntoIter<EventResponse>> =
+            slices.into_iter().map(|slice| slice.into_iter()).collect();
+        let mut heads: Vec<Option<EventResponse>> =
+            slices.iter_mut().map(|slice| slice.next()).collect();
+
+        let mut events: Vec<EventResponse> = vec![];
+        while events.len() < max_entries {
+            // Take the first event of the child canister whose next event comes first in the requested order
+            let next = heads
+                .iter()
+                .enumerate()
+                .filter_map(|(index, head)| head.as_ref().map(|event| (index, event)))
+                .min_by(|(_, a), (_, b)| compare_events(a, b, sort))
+                .map(|(index, _)| index);
+
+            match next {
+                None => break,
+                Some(index) => {
+                    if let Some(event) = heads[index].take() {
+                        events.push(event);
+                    }
+                    heads[index] = slices[index].next();
+                }
+            }
+        }
 
         events
     }
@@ -535,29 +1171,38 @@ impl ScalableData {
     // Inter canister call to fetch the chunked data from the child canister
     async fn get_chunked_child_data(
         canister_principal: Principal,
-        filters: &Vec<EventFilter>,
-        filter_type: &FilterType,
+        request: &ChunkedDataRequest,
+        snapshot: Option<ChunkSnapshot>,
         chunk: usize,
         max_bytes_per_chunk: Option<usize>,
-    ) -> (Vec<u8>, (usize, usize)) {
+    ) -> Result<ChunkedData, ApiError> {
         // If the max bytes per chunk is not provided, use the default of 2_000_000 (2mb)
         let _max_bytes_per_chunk = max_bytes_per_chunk.unwrap_or(2_000_000);
-        let result: Result<(Vec<u8>, (usize, usize)), _> = call::call(
+        let result: Result<(Result<ChunkedData, ApiError>,), _> = call::call(
             canister_principal,
             "get_chunked_data",
-            (filters, filter_type, chunk, _max_bytes_per_chunk),
+            (request, snapshot, chunk, _max_bytes_per_chunk),
         )
         .await;
 
-        // return the bytes and the chunk info, if there is an error, return an empty vec and chunk info
         match result {
-            Ok(_res) => _res,
-            _ => (vec![], (0, 0)),
+            Ok((_res,)) => _res,
+            Err(err) => Err(api_error(
+                ApiErrorType::BadRequest,
+                "FAILED_TO_FETCH_CHUNK",
+                err.1.as_str(),
+                &Self::get_name(),
+                "get_chunked_child_data",
+                Some(vec![
+                    format!("canister - {}", &canister_principal.to_string()),
+                    format!("chunk - {}", &chunk),
+                ]),
+            )),
         }
     }
 
     // Method mostly used for usage in error handling
-    fn get_name() -> String {
+    pub(crate) fn get_name() -> String {
         DATA.with(|v| v.borrow().name.clone())
     }
 }
@@ -0,0 +1,43 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::event_facets::EventFacetRequest;
+use crate::event_models::{EventFilterExpression, EventSort};
+
+// The events the parent canister requests from a child canister
+#[derive(CandidType, Deserialize)]
+pub struct ChunkedDataRequest {
+    pub filter: EventFilterExpression,
+    pub group_identifier: Option<Principal>,
+    pub sort: Vec<EventSort>,
+    // The facets that are counted over all the events that match the filter
+    pub facets: Option<EventFacetRequest>,
+    // The amount of events in the requested order that are returned
+    pub max_entries: usize,
+}
+
+// Handle of the serialized events of a child canister, created on the first chunk call and passed along with the next chunk calls
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub struct ChunkSnapshot {
+    // The data version of the child canister when the snapshot was created
+    pub id: u64,
+    pub total_bytes: usize,
+    pub checksum: String,
+    pub created_on: u64,
+}
+
+impl ChunkSnapshot {
+    // Method to calculate the checksum of the serialized events (hex encoded sha256)
+    pub fn checksum(bytes: &[u8]) -> String {
+        hex::encode(Sha256::digest(bytes))
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct ChunkedData {
+    pub snapshot: ChunkSnapshot,
+    pub chunk: usize,
+    pub total_chunks: usize,
+    pub bytes: Vec<u8>,
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+use crate::event_models::EventResponse;
+
+// The facets that are counted over all the events that match the filter
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct EventFacetRequest {
+    pub privacy: bool,
+    pub location_type: bool,
+    pub tags: bool,
+    pub canceled: bool,
+    // Month of the start date formatted as `YYYY-MM` (UTC)
+    pub month: bool,
+}
+
+// The counts per facet value, only the requested facets are set
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct EventFacets {
+    pub privacy: Option<Vec<(String, usize)>>,
+    pub location_type: Option<Vec<(String, usize)>>,
+    pub tags: Option<Vec<(u32, usize)>>,
+    pub canceled: Option<Vec<(bool, usize)>>,
+    pub month: Option<Vec<(String, usize)>>,
+}
+
+// Used to count the facets while filtering the events and to merge the facets of multiple child canisters
+#[derive(Default)]
+pub struct EventFacetCounter {
+    privacy: Option<HashMap<String, usize>>,
+    location_type: Option<HashMap<String, usize>>,
+    tags: Option<HashMap<u32, usize>>,
+    canceled: Option<HashMap<bool, usize>>,
+    month: Option<HashMap<String, usize>>,
+}
+
+impl EventFacetCounter {
+    pub fn new(request: &EventFacetRequest) -> Self {
+        Self {
+            privacy: request.privacy.then(HashMap::new),
+            location_type: request.location_type.then(HashMap::new),
+            tags: request.tags.then(HashMap::new),
+            canceled: request.canceled.then(HashMap::new),
+            month: request.month.then(HashMap::new),
+        }
+    }
+
+    // Method to count the facet values of an event
+    pub fn add(&mut self, event: &EventResponse) {
+        if let Some(counts) = self.privacy.as_mut() {
+            increment(counts, variant_name(&event.privacy), 1);
+        }
+        if let Some(counts) = self.location_type.as_mut() {
+            increment(counts, variant_name(&event.location), 1);
+        }
+        if let Some(counts) = self.tags.as_mut() {
+            let mut tags = event.tags.clone();
+            tags.sort();
+            tags.dedup();
+            for tag in tags {
+                increment(counts, tag, 1);
+            }
+        }
+        if let Some(counts) = self.canceled.as_mut() {
+            increment(counts, event.is_canceled.0, 1);
+        }
+        if let Some(counts) = self.month.as_mut() {
+            increment(counts, month_of(event.date.start_date), 1);
+        }
+    }
+
+    // Method to add the facets counted on another canister
+    pub fn merge(&mut self, facets: EventFacets) {
+        merge_counts(&mut self.privacy, facets.privacy);
+        merge_counts(&mut self.location_type, facets.location_type);
+        merge_counts(&mut self.tags, facets.tags);
+        merge_counts(&mut self.canceled, facets.canceled);
+        merge_counts(&mut self.month, facets.month);
+    }
+
+    pub fn into_facets(self) -> EventFacets {
+        EventFacets {
+            privacy: self.privacy.map(|counts| counts.into_iter().collect()),
+            location_type: self
+                .location_type
+                .map(|counts| counts.into_iter().collect()),
+            tags: self.tags.map(|counts| counts.into_iter().collect()),
+            canceled: self.canceled.map(|counts| counts.into_iter().collect()),
+            month: self.month.map(|counts| counts.into_iter().collect()),
+        }
+    }
+}
+
+fn increment<K: Eq + Hash>(counts: &mut HashMap<K, usize>, key: K, amount: usize) {
+    *counts.entry(key).or_insert(0) += amount;
+}
+
+fn merge_counts<K: Eq + Hash>(
+    counts: &mut Option<HashMap<K, usize>>,
+    other: Option<Vec<(K, usize)>>,
+) {
+    if let (Some(counts), Some(other)) = (counts.as_mut(), other) {
+        for (key, amount) in other {
+            increment(counts, key, amount);
+        }
+    }
+}
+
+// Method to get the name of an enum variant without its data (`Gated(..)` becomes `Gated`)
+fn variant_name<T: Debug>(value: &T) -> String {
+    let name = format!("{:?}", value);
+    match name.find(['(', '{', ' ']) {
+        Some(index) => name[..index].to_string(),
+        None => name,
+    }
+}
+
+// Method to get the month of a timestamp (nanoseconds) formatted as `YYYY-MM` (UTC)
+pub fn month_of(timestamp: u64) -> String {
+    let (year, month, _) = civil_from_days((timestamp / 1_000_000_000 / 86_400) as i64);
+    format!("{:04}-{:02}", year, month)
+}
+
+// Method to convert the days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,101 @@
+use ic_scalable_canister::ic_scalable_misc::{
+    enums::filter_type::FilterType, models::date_models::DateRange,
+};
+
+use crate::event_models::{EventFilter, EventFilterExpression, EventProfile, EventResponse};
+
+// A day in nanoseconds
+static DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+impl EventFilterExpression {
+    // Method to build a filter expression from a flat list of filters combined with a single filter type
+    pub fn from_filters(filters: Vec<EventFilter>, filter_type: FilterType) -> Self {
+        let expressions = filters.into_iter().map(Self::Filter).collect();
+        match filter_type {
+            FilterType::And => Self::And(expressions),
+            FilterType::Or => Self::Or(expressions),
+        }
+    }
+
+    // Method to check if an event matches the filter expression, `now` is the time of the canister (nanoseconds)
+    // an empty `And` or `Or` matches every event
+    pub fn matches(&self, event: &EventResponse, now: u64) -> bool {
+        use EventFilterExpression::*;
+        match self {
+            Filter(filter) => filter.matches(event, now),
+            And(expressions) => expressions
+                .iter()
+                .all(|expression| expression.matches(event, now)),
+            Or(expressions) => {
+                expressions.is_empty()
+                    || expressions
+                        .iter()
+                        .any(|expression| expression.matches(event, now))
+            }
+            Not(expression) => !expression.matches(event, now),
+        }
+    }
+}
+
+impl Default for EventFilterExpression {
+    fn default() -> Self {
+        Self::And(vec![])
+    }
+}
+
+impl EventFilter {
+    // Method to check if an event matches a single filter, `now` is the time of the canister (nanoseconds)
+    pub fn matches(&self, event: &EventResponse, now: u64) -> bool {
+        use EventFilter::*;
+        match self {
+            Identifiers(value) => value.contains(&event.identifier),
+            Name(value) => event.name.contains(value),
+            StartDate(value) => is_in_range(event.date.start_date, value),
+            EndDate(value) => is_in_range(event.date.end_date, value),
+            Owner(value) => &event.owner == value,
+            Tag(value) => event.tags.contains(value),
+            UpdatedOn(value) => is_in_range(event.updated_on, value),
+            CreatedOn(value) => is_in_range(event.created_on, value),
+            IsCanceled(value) => value == &event.is_canceled.0,
+            Upcoming => event.date.start_date > now,
+            Ongoing => {
+                event.date.start_date <= now
+                    && (event.date.end_date == 0 || event.date.end_date >= now)
+            }
+            Past => event.date.end_date > 0 && event.date.end_date < now,
+            Tags {
+                any_of,
+                all_of,
+                none_of,
+            } => {
+                (any_of.is_empty() || any_of.iter().any(|tag| event.tags.contains(tag)))
+                    && all_of.iter().all(|tag| event.tags.contains(tag))
+                    && !none_of.iter().any(|tag| event.tags.contains(tag))
+            }
+            StartsWithinDays(days) => {
+                event.date.start_date >= now
+                    && event.date.start_date <= now.saturating_add(days.saturating_mul(DAY))
+            }
+            Metadata { key, op, value } => match event.typed_metadata.get(key) {
+                Some(_value) => _value.matches(op, value),
+                None => false,
+            },
+            Speaker(value) => {
+                EventProfile::has_principal(&event.speakers, value)
+                    || event
+                        .sessions
+                        .iter()
+                        .any(|session| session.speakers.contains(value))
+            }
+            Sponsor(value) => EventProfile::has_principal(&event.sponsors, value),
+        }
+    }
+}
+
+// Method to check if a timestamp is within the date range, a range without an end date has no upper bound
+fn is_in_range(timestamp: u64, range: &DateRange) -> bool {
+    if range.end_date > 0 {
+        return timestamp >= range.start_date && timestamp <= range.end_date;
+    }
+    timestamp >= range.start_date
+}
@@ -0,0 +1,86 @@
+use candid::Principal;
+
+use crate::event_facets::civil_from_days;
+use crate::event_models::{EventSession, SessionLocation};
+
+impl EventSession {
+    // Method to export the session as an iCalendar (RFC 5545) file with a single event, `now` is the time of the canister (nanoseconds)
+    pub fn to_ics(&self, event_identifier: &Principal, event_name: &str, now: u64) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Catalyze//Events//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-{}@catalyze.one", self.id, event_identifier),
+            format!("DTSTAMP:{}", ics_date_time(now)),
+            format!("DTSTART:{}", ics_date_time(self.date.start_date)),
+        ];
+
+        if self.date.end_date > 0 {
+            lines.push(format!("DTEND:{}", ics_date_time(self.date.end_date)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&self.title)));
+        if !self.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&self.description)));
+        }
+        match &self.location {
+            SessionLocation::Room(room) => lines.push(format!("LOCATION:{}", escape_text(room))),
+            SessionLocation::Digital(link) => {
+                lines.push(format!("LOCATION:{}", escape_text(link)));
+                lines.push(format!("URL:{}", link));
+            }
+        }
+        lines.push(format!("CATEGORIES:{}", escape_text(event_name)));
+        lines.push("END:VEVENT".to_string());
+        lines.push("END:VCALENDAR".to_string());
+
+        lines
+            .iter()
+            .map(|line| fold_line(line))
+            .collect::<Vec<String>>()
+            .join("\r\n")
+            + "\r\n"
+    }
+}
+
+// Format a timestamp (nanoseconds) as an UTC date-time, for example `20240131T093000Z`
+fn ics_date_time(timestamp: u64) -> String {
+    let seconds = timestamp / 1_000_000_000;
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60
+    )
+}
+
+// Escape the characters that have a meaning in text values
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 bytes are folded, the continuation lines start with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for character in line.chars() {
+        if length + character.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(character);
+        length += character.len_utf8();
+    }
+    folded
+}
@@ -0,0 +1,51 @@
+use std::cmp::Ordering;
+
+use crate::event_models::{MetadataFilterOp, MetadataValue, MetadataValueType};
+
+impl MetadataValue {
+    pub fn value_type(&self) -> MetadataValueType {
+        use MetadataValue::*;
+        match self {
+            Text(_) => MetadataValueType::Text,
+            Number(_) => MetadataValueType::Number,
+            Bool(_) => MetadataValueType::Bool,
+            Url(_) => MetadataValueType::Url,
+        }
+    }
+
+    // Method to check if the stored value matches the filter operation and value
+    // values of a different type never match, except for `Exists`
+    pub fn matches(&self, op: &MetadataFilterOp, value: &MetadataValue) -> bool {
+        use MetadataFilterOp::*;
+        match op {
+            Exists => true,
+            Equals => self == value,
+            NotEquals => self.value_type() == value.value_type() && self != value,
+            GreaterThan => self.compare(value) == Some(Ordering::Greater),
+            LessThan => self.compare(value) == Some(Ordering::Less),
+            Contains => match (self.as_text(), value.as_text()) {
+                (Some(_self), Some(_value)) => {
+                    _self.to_lowercase().contains(&_value.to_lowercase())
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn compare(&self, other: &MetadataValue) -> Option<Ordering> {
+        use MetadataValue::*;
+        match (self, other) {
+            (Number(a), Number(b)) => a.partial_cmp(b),
+            (Text(a), Text(b)) | (Url(a), Url(b)) => Some(a.cmp(b)),
+            (Bool(a), Bool(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            MetadataValue::Text(value) | MetadataValue::Url(value) => Some(value),
+            _ => None,
+        }
+    }
+}
@@ -1,15 +1,20 @@
 use std::{borrow::Cow, collections::HashMap};
 
-use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use candid::{CandidType, Decode, Deserialize, Encode, Nat, Principal};
 use ic_scalable_canister::ic_scalable_misc::{
     enums::{
-        asset_type::Asset, location_type::Location, privacy_type::Privacy, sort_type::SortDirection,
+        api_error_type::ApiError, asset_type::Asset, location_type::Location,
+        privacy_type::Privacy, sort_type::SortDirection,
     },
-    models::date_models::DateRange,
+    models::{date_models::DateRange, paged_response_models::PagedResponse},
     traits::stable_storage_trait::StableStorableTrait,
 };
 use ic_stable_structures::{storable::Bound, Storable};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::event_facets::EventFacets;
+use crate::icrc_models::{Account, Subaccount};
 
 #[derive(Clone, CandidType, Serialize, Deserialize, Debug)]
 pub struct Event {
@@ -28,9 +33,40 @@ pub struct Event {
     pub tags: Vec<u32>,
     pub is_canceled: (bool, String),
     pub is_deleted: bool,
+    // An event is pending until the owner is added as an attendee, pending events aren't visible
+    #[serde(default)]
+    pub is_pending: bool,
     pub attendee_count: HashMap<Principal, usize>,
     #[serde(default)]
     pub metadata: Option<String>,
+    // Typed metadata, validated against the metadata schema of the group
+    #[serde(default)]
+    pub typed_metadata: HashMap<String, MetadataValue>,
+    // The pending ownership transfer that needs to be accepted by the new owner
+    #[serde(default)]
+    pub owner_transfer: Option<OwnerTransfer>,
+    // Audit trail of the ownership transfers
+    #[serde(default)]
+    pub owner_transfer_history: Vec<OwnerTransferRecord>,
+    // Co-hosts and moderators with their abilities on this event
+    #[serde(default)]
+    pub hosts: Vec<EventHost>,
+    // Increased on every change by an organiser, used to detect concurrent edits
+    #[serde(default)]
+    pub version: u64,
+    // The ticket tiers with the amount of tickets sold per tier, the holders are stored on the event attendee canister
+    #[serde(default)]
+    pub ticket_tiers: Vec<TicketTier>,
+    // The sessions on the agenda of the event, ordered by their start date
+    #[serde(default)]
+    pub sessions: Vec<EventSession>,
+    #[serde(default)]
+    pub speakers: Vec<EventProfile>,
+    #[serde(default)]
+    pub sponsors: Vec<EventProfile>,
+    // The sibling canister that forwarded this pending event, only that canister can finalize or remove it
+    #[serde(default)]
+    pub forwarded_by: Option<Principal>,
     pub updated_on: u64,
     pub created_on: u64,
 }
@@ -66,14 +102,403 @@ impl Default for Event {
             tags: Default::default(),
             is_canceled: Default::default(),
             is_deleted: Default::default(),
+            is_pending: Default::default(),
             attendee_count: Default::default(),
             updated_on: Default::default(),
             created_on: Default::default(),
             metadata: Default::default(),
+            typed_metadata: Default::default(),
+            owner_transfer: Default::default(),
+            owner_transfer_history: Default::default(),
+            hosts: Default::default(),
+            version: Default::default(),
+            ticket_tiers: Default::default(),
+            sessions: Default::default(),
+            speakers: Default::default(),
+            sponsors: Default::default(),
+            forwarded_by: Default::default(),
         }
     }
 }
 
+impl Event {
+    // Method to get a hex encoded sha256 fingerprint of the event, used to detect changes when events are moved between canisters
+    // the maps are sorted because the order of a hashmap isnt stable
+    pub fn fingerprint(&self) -> String {
+        let mut event = self.clone();
+
+        let mut attendee_count: Vec<(Principal, usize)> = event.attendee_count.drain().collect();
+        attendee_count.sort();
+
+        let mut typed_metadata: Vec<(String, MetadataValue)> =
+            event.typed_metadata.drain().collect();
+        typed_metadata.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let bytes = Encode!(&event, &attendee_count, &typed_metadata).unwrap_or_default();
+        hex::encode(Sha256::digest(bytes))
+    }
+}
+
+// A typed metadata value, urls are validated as http(s) urls
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub enum MetadataValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Url(String),
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub enum MetadataValueType {
+    Text,
+    Number,
+    Bool,
+    Url,
+}
+
+// A key of the metadata schema of a group
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct MetadataField {
+    pub key: String,
+    pub value_type: MetadataValueType,
+    pub required: bool,
+}
+
+// The metadata schema of a group, registered by the group admins
+// when a group has a schema only the keys in the schema are allowed
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct MetadataSchema {
+    pub group_identifier: Principal,
+    pub fields: Vec<MetadataField>,
+    pub updated_by: Principal,
+    pub updated_on: u64,
+}
+
+impl Storable for MetadataSchema {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum MetadataFilterOp {
+    Equals,
+    NotEquals,
+    // Numbers are compared numerically, text and urls alphabetically
+    GreaterThan,
+    LessThan,
+    // Case-insensitive, only for text and urls
+    Contains,
+    // The value is ignored
+    Exists,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub enum EventHostRole {
+    CoHost,
+    Moderator,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub enum EventHostAbility {
+    EditDetails,
+    Cancel,
+    CheckInAttendees,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct EventHost {
+    pub principal: Principal,
+    pub role: EventHostRole,
+    pub abilities: Vec<EventHostAbility>,
+    pub added_by: Principal,
+    pub created_on: u64,
+}
+
+impl EventHost {
+    // Method to find the host entry of a principal
+    pub fn find<'a>(hosts: &'a [EventHost], principal: &Principal) -> Option<&'a EventHost> {
+        hosts.iter().find(|host| &host.principal == principal)
+    }
+
+    // Method to check if the principal is a host of the event with the ability
+    pub fn has_ability(
+        hosts: &[EventHost],
+        principal: &Principal,
+        ability: &EventHostAbility,
+    ) -> bool {
+        Self::find(hosts, principal).is_some_and(|host| host.abilities.contains(ability))
+    }
+
+    // Method to check if the principal can delete the event, only co-hosts that can cancel the event are allowed to delete it
+    pub fn can_delete(hosts: &[EventHost], principal: &Principal) -> bool {
+        Self::find(hosts, principal).is_some_and(|host| {
+            host.role == EventHostRole::CoHost && host.abilities.contains(&EventHostAbility::Cancel)
+        })
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct OwnerTransfer {
+    pub from: Principal,
+    pub to: Principal,
+    pub proposed_by: Principal,
+    pub expires_at: u64,
+    pub created_on: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum OwnerTransferAction {
+    Proposed,
+    Accepted,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct OwnerTransferRecord {
+    pub action: OwnerTransferAction,
+    pub from: Principal,
+    pub to: Principal,
+    // The principal that proposed or accepted the transfer
+    pub by: Principal,
+    pub created_on: u64,
+}
+
+// The price of a ticket in an ICRC-1 token, the amount is in the smallest unit of the token
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq)]
+pub struct TicketPrice {
+    pub ledger: Principal,
+    pub amount: Nat,
+}
+
+impl TicketPrice {
+    pub fn is_free(&self) -> bool {
+        self.amount == 0_u64
+    }
+}
+
+// A ticket tier of an event, an end of the sale window of 0 means the sale has no end
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TicketTier {
+    pub id: u32,
+    pub name: String,
+    pub price: TicketPrice,
+    pub supply: u64,
+    pub sold: u64,
+    pub sale_start: u64,
+    pub sale_end: u64,
+    pub created_on: u64,
+}
+
+impl TicketTier {
+    pub fn find(tiers: &[TicketTier], id: u32) -> Option<&TicketTier> {
+        tiers.iter().find(|tier| tier.id == id)
+    }
+
+    pub fn available(&self) -> u64 {
+        self.supply.saturating_sub(self.sold)
+    }
+
+    // A tier is on sale within the sale window while there are tickets available
+    pub fn is_on_sale(&self, now: u64) -> bool {
+        now >= self.sale_start
+            && (self.sale_end == 0 || now <= self.sale_end)
+            && self.available() > 0
+    }
+
+    pub fn to_response(&self, now: u64) -> TicketTierResponse {
+        TicketTierResponse {
+            id: self.id,
+            name: self.name.clone(),
+            price: self.price.clone(),
+            supply: self.supply,
+            sold: self.sold,
+            available: self.available(),
+            sale_start: self.sale_start,
+            sale_end: self.sale_end,
+            is_on_sale: self.is_on_sale(now),
+        }
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct PostTicketTier {
+    pub name: String,
+    pub price: TicketPrice,
+    pub supply: u64,
+    pub sale_start: u64,
+    pub sale_end: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TicketTierResponse {
+    pub id: u32,
+    pub name: String,
+    pub price: TicketPrice,
+    pub supply: u64,
+    pub sold: u64,
+    pub available: u64,
+    pub sale_start: u64,
+    pub sale_end: u64,
+    pub is_on_sale: bool,
+}
+
+// A ticket that is issued to a holder, the holder is recorded on the event attendee canister
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Ticket {
+    pub event_identifier: Principal,
+    pub tier_id: u32,
+    pub holder: Principal,
+    pub price: TicketPrice,
+    pub created_on: u64,
+}
+
+// How a paid ticket is paid, with an ICRC-1 transfer to the payment subaccount of the buyer (`get_ticket_payment_account`)
+// or with an ICRC-2 approval for the child canister to transfer the price (plus fee) from the account of the buyer
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum TicketPaymentMethod {
+    Icrc1Transfer,
+    Icrc2Approval { from_subaccount: Option<Subaccount> },
+}
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum TicketPaymentStatus {
+    // The payment is sent to the ledger, an interrupted payment is resubmitted with the same created_at_time and memo
+    Collecting { created_at_time: u64 },
+    Paid,
+    // The refund is sent to the ledger, an interrupted refund is resubmitted with the same created_at_time and memo
+    Refunding { created_at_time: u64 },
+    Refunded { block_index: Nat, refunded_on: u64 },
+    RefundFailed(String),
+}
+
+// A collected ticket payment, the amount is held on the subaccount of the event until it is refunded
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct TicketPayment {
+    pub event_identifier: Principal,
+    pub tier_id: u32,
+    pub payer: Principal,
+    pub refund_account: Account,
+    pub payment_method: TicketPaymentMethod,
+    pub ledger: Principal,
+    pub amount: Nat,
+    // The block of the collected payment, `None` while the payment is collecting
+    pub block_index: Option<Nat>,
+    pub status: TicketPaymentStatus,
+    pub created_on: u64,
+}
+
+impl Storable for TicketPayment {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+// The attendance of an attendee at a physical event, recorded when a host scans the check-in code
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct CheckIn {
+    pub event_identifier: Principal,
+    pub attendee: Principal,
+    pub checked_in_by: Principal,
+    pub checked_in_on: u64,
+}
+
+impl Storable for CheckIn {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct CheckInStats {
+    pub registered: usize,
+    pub checked_in: usize,
+}
+
+// Where a session takes place, a room at the location of the event or a link for digital sessions
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub enum SessionLocation {
+    Room(String),
+    Digital(String),
+}
+
+// A session on the agenda of an event, the time slot falls within the dates of the event
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct EventSession {
+    pub id: u32,
+    pub title: String,
+    pub description: String,
+    pub speakers: Vec<Principal>,
+    pub date: DateRange,
+    pub location: SessionLocation,
+    pub tags: Vec<u32>,
+    pub updated_on: u64,
+    pub created_on: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct PostEventSession {
+    pub title: String,
+    pub description: String,
+    pub speakers: Vec<Principal>,
+    pub date: DateRange,
+    pub location: SessionLocation,
+    pub tags: Vec<u32>,
+}
+
+// A speaker or sponsor shown on the event page, the principal links the profile to a user or organisation
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize)]
+pub struct EventProfile {
+    pub id: u32,
+    pub name: String,
+    pub principal: Option<Principal>,
+    pub bio: String,
+    pub image: Asset,
+    pub link: String,
+    pub updated_on: u64,
+    pub created_on: u64,
+}
+
+impl EventProfile {
+    pub fn has_principal(profiles: &[EventProfile], principal: &Principal) -> bool {
+        profiles
+            .iter()
+            .any(|profile| profile.principal.as_ref() == Some(principal))
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct PostEventProfile {
+    pub name: String,
+    pub principal: Option<Principal>,
+    pub bio: String,
+    pub image: Asset,
+    pub link: String,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum EventProfileKind {
+    Speaker,
+    Sponsor,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct PostEvent {
     pub name: String,
@@ -86,6 +511,9 @@ pub struct PostEvent {
     pub owner: Principal,
     pub banner_image: Asset,
     pub metadata: Option<String>,
+    // Typed metadata, validated against the metadata schema of the group
+    #[serde(default)]
+    pub typed_metadata: HashMap<String, MetadataValue>,
     pub tags: Vec<u32>,
 }
 
@@ -98,12 +526,32 @@ pub struct UpdateEvent {
     pub website: String,
     pub location: Location,
     pub image: Asset,
-    pub owner: Principal,
     pub banner_image: Asset,
     pub metadata: Option<String>,
+    // Typed metadata, validated against the metadata schema of the group
+    #[serde(default)]
+    pub typed_metadata: HashMap<String, MetadataValue>,
     pub tags: Vec<u32>,
 }
 
+// Partial update of an event, only the fields that are set are validated and changed
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct EventPatch {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub date: Option<DateRange>,
+    pub privacy: Option<Privacy>,
+    pub website: Option<String>,
+    pub location: Option<Location>,
+    pub image: Option<Asset>,
+    pub banner_image: Option<Asset>,
+    // `Some(None)` clears the metadata
+    pub metadata: Option<Option<String>>,
+    // Replaces the typed metadata when set
+    pub typed_metadata: Option<HashMap<String, MetadataValue>>,
+    pub tags: Option<Vec<u32>>,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub enum EventSort {
     CreatedOn(SortDirection),
@@ -111,6 +559,7 @@ pub enum EventSort {
     StartDate(SortDirection),
     EndDate(SortDirection),
     AttendeeCount(SortDirection),
+    Name(SortDirection),
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
@@ -124,6 +573,36 @@ pub enum EventFilter {
     IsCanceled(bool),
     UpdatedOn(DateRange),
     CreatedOn(DateRange),
+    // Relative to the time of the canister, events without an end date are never in the past
+    Upcoming,
+    Ongoing,
+    Past,
+    // Events that start between now and the amount of days from now
+    StartsWithinDays(u64),
+    // Events that have at least one of `any_of`, all of `all_of` and none of `none_of`, empty lists are ignored
+    Tags {
+        any_of: Vec<u32>,
+        all_of: Vec<u32>,
+        none_of: Vec<u32>,
+    },
+    // Events with a typed metadata value for the key that matches the operation
+    Metadata {
+        key: String,
+        op: MetadataFilterOp,
+        value: MetadataValue,
+    },
+    // Events where the principal is a speaker of the event or of one of its sessions
+    Speaker(Principal),
+    Sponsor(Principal),
+}
+
+// Filters combined into a tree, for example `And([Or([Tag(3), Tag(7)]), Not(Filter(IsCanceled(true)))])`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum EventFilterExpression {
+    Filter(EventFilter),
+    And(Vec<EventFilterExpression>),
+    Or(Vec<EventFilterExpression>),
+    Not(Box<EventFilterExpression>),
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
@@ -144,7 +623,77 @@ pub struct EventResponse {
     pub is_deleted: bool,
     pub tags: Vec<u32>,
     pub metadata: Option<String>,
+    // Typed metadata, validated against the metadata schema of the group
+    #[serde(default)]
+    pub typed_metadata: HashMap<String, MetadataValue>,
+    #[serde(default)]
+    pub hosts: Vec<EventHost>,
+    #[serde(default)]
+    pub version: u64,
+    // The ticket tiers with their availability at the time of the request
+    #[serde(default)]
+    pub ticket_tiers: Vec<TicketTierResponse>,
+    #[serde(default)]
+    pub tickets_sold: u64,
+    #[serde(default)]
+    pub sessions: Vec<EventSession>,
+    #[serde(default)]
+    pub speakers: Vec<EventProfile>,
+    #[serde(default)]
+    pub sponsors: Vec<EventProfile>,
     pub updated_on: u64,
     pub created_on: u64,
     pub group_identifier: Principal,
 }
+
+// Response of the add method, contains the canister that stores the event
+// which is a sibling canister when this canister was at capacity
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AddEventResponse {
+    pub canister: Principal,
+    pub event: EventResponse,
+}
+
+// Error returned by the edit methods, a conflict contains the current state of the event
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum EditEventError {
+    Conflict(Box<EventResponse>),
+    ApiError(ApiError),
+}
+
+impl From<ApiError> for EditEventError {
+    fn from(err: ApiError) -> Self {
+        Self::ApiError(err)
+    }
+}
+
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct EventsCount {
+    pub total: usize,
+    pub breakdown: Option<EventsCountBreakdown>,
+}
+
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct EventsCountBreakdown {
+    pub upcoming: usize,
+    pub past: usize,
+    pub cancelled: usize,
+}
+
+// The first events of a child canister in the requested order, used by the parent canister to merge the events of all the child canisters
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct EventsSlice {
+    // The total amount of events that matched the filters
+    pub total: usize,
+    pub events: Vec<EventResponse>,
+    // The facets counted over all the events that matched the filters
+    #[serde(default)]
+    pub facets: Option<EventFacets>,
+}
+
+// A page of events with the facets counted over all the events that matched the filters
+#[derive(CandidType, Deserialize)]
+pub struct PagedEventsResponse {
+    pub events: PagedResponse<EventResponse>,
+    pub facets: Option<EventFacets>,
+}
@@ -0,0 +1,47 @@
+use std::cmp::Ordering;
+
+use ic_scalable_canister::ic_scalable_misc::enums::sort_type::SortDirection;
+
+use crate::event_models::{EventResponse, EventSort};
+
+// Method to sort the events on the passed sort keys, the first key has the highest priority
+// events that are equal on all the sort keys are ordered by their identifier so the order is always stable
+pub fn sort_events(mut events: Vec<EventResponse>, sort: &[EventSort]) -> Vec<EventResponse> {
+    events.sort_by(|a, b| compare_events(a, b, sort));
+    events
+}
+
+// Method to compare two events on the passed sort keys, falls back to the identifier when all the keys are equal
+pub fn compare_events(a: &EventResponse, b: &EventResponse, sort: &[EventSort]) -> Ordering {
+    sort.iter()
+        .map(|key| compare_on_key(a, b, key))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| a.identifier.as_slice().cmp(b.identifier.as_slice()))
+}
+
+// Method to compare two events on a single sort key
+fn compare_on_key(a: &EventResponse, b: &EventResponse, sort: &EventSort) -> Ordering {
+    use EventSort::*;
+    let (ordering, direction) = match sort {
+        CreatedOn(direction) => (a.created_on.cmp(&b.created_on), direction),
+        UpdatedOn(direction) => (a.updated_on.cmp(&b.updated_on), direction),
+        StartDate(direction) => (a.date.start_date.cmp(&b.date.start_date), direction),
+        EndDate(direction) => (a.date.end_date.cmp(&b.date.end_date), direction),
+        AttendeeCount(direction) => (a.attendee_count.cmp(&b.attendee_count), direction),
+        Name(direction) => (compare_names(&a.name, &b.name), direction),
+    };
+
+    match direction {
+        SortDirection::Asc => ordering,
+        SortDirection::Desc => ordering.reverse(),
+    }
+}
+
+// Method to compare names independent of the locale and casing ("apple" < "Banana" < "cherry")
+// names that only differ in casing are ordered by their code points
+fn compare_names(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+        .then_with(|| a.cmp(b))
+}
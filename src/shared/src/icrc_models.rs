@@ -0,0 +1,122 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// The subset of the ICRC-1 and ICRC-2 ledger interface that is used to collect and refund ticket payments
+// https://github.com/dfinity/ICRC-1/tree/main/standards
+
+pub type Subaccount = Vec<u8>;
+
+#[derive(Clone, Debug, CandidType, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<Subaccount>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct TransferArg {
+    pub from_subaccount: Option<Subaccount>,
+    pub to: Account,
+    pub fee: Option<Nat>,
+    pub created_at_time: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+    pub amount: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum TransferError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    TemporarilyUnavailable,
+    Duplicate { duplicate_of: Nat },
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ApproveArgs {
+    pub from_subaccount: Option<Subaccount>,
+    pub spender: Account,
+    pub amount: Nat,
+    pub expected_allowance: Option<Nat>,
+    pub expires_at: Option<u64>,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum ApproveError {
+    BadFee { expected_fee: Nat },
+    InsufficientFunds { balance: Nat },
+    AllowanceChanged { current_allowance: Nat },
+    Expired { ledger_time: u64 },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct TransferFromArgs {
+    pub spender_subaccount: Option<Subaccount>,
+    pub from: Account,
+    pub to: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum TransferFromError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AllowanceArgs {
+    pub account: Account,
+    pub spender: Account,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Allowance {
+    pub allowance: Nat,
+    pub expires_at: Option<u64>,
+}
+
+// The subaccount of the child canister that holds the ticket payments of an event, refunds are paid from this subaccount
+pub fn event_subaccount(event_identifier: &Principal) -> Subaccount {
+    subaccount(&[b"event", event_identifier.as_slice()])
+}
+
+// The subaccount of the child canister a buyer transfers the ticket price to when paying with an ICRC-1 transfer
+pub fn ticket_payment_subaccount(event_identifier: &Principal, payer: &Principal) -> Subaccount {
+    subaccount(&[b"ticket", event_identifier.as_slice(), payer.as_slice()])
+}
+
+// The memo of the transfers of a ticket payment, a resubmitted transfer with the same memo and created_at_time is deduplicated by the ledger
+pub fn ticket_payment_memo(payment_key: &str) -> Vec<u8> {
+    Sha256::digest(payment_key.as_bytes()).to_vec()
+}
+
+// The parts are length prefixed so different parts can't result in the same subaccount
+fn subaccount(parts: &[&[u8]]) -> Subaccount {
+    let mut hasher = Sha256::new();
+    parts.iter().for_each(|part| {
+        hasher.update([part.len() as u8]);
+        hasher.update(part);
+    });
+    hasher.finalize().to_vec()
+}
@@ -1 +1,10 @@
+pub static IDENTIFIER_KIND: &str = "evt";
+
+pub mod chunk_models;
+pub mod event_facets;
+pub mod event_filter;
+pub mod event_ics;
+pub mod event_metadata;
 pub mod event_models;
+pub mod event_sort;
+pub mod icrc_models;